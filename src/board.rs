@@ -0,0 +1,387 @@
+//! Compare a netlist with a KiCad board
+//!
+//! The footprints and pad nets are read from a `.kicad_pcb` file and compared with the netlist, in the same way as a
+//! dry run of "Update PCB from Schematic". Components without a footprint or marked `exclude_from_board` are not
+//! expected on the board, and footprints marked as board only, such as mounting holes, are not expected in the
+//! netlist.
+
+use std::fmt::Display;
+
+use crate::export::board_netlist;
+use crate::sexpr::SExpr;
+use crate::{Footprint, NetList, NetName, ParseError, PinNum, RefDes, Value};
+
+/// A pad of a footprint on the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardPad<'a> {
+    /// The pad number, which is empty for pads which do not connect to a pin
+    pub num: PinNum<'a>,
+    /// The net assigned to the pad, if any
+    pub net: Option<NetName<'a>>,
+}
+
+/// A footprint placed on the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardFootprint<'a> {
+    pub ref_des: RefDes<'a>,
+    pub value: Value<'a>,
+    pub footprint: Footprint<'a>,
+    /// The uuid path of the symbol the footprint was created from
+    pub path: Option<&'a str>,
+    /// The footprint only exists on the board and has no symbol
+    pub board_only: bool,
+    pub pads: Vec<BoardPad<'a>>,
+}
+
+impl<'a> BoardFootprint<'a> {
+    /// The net of the first pad with the number
+    pub fn pad_net(&self, num: PinNum<'_>) -> Option<NetName<'a>> {
+        self.pads
+            .iter()
+            .find(|pad| pad.num.as_str() == num.as_str())
+            .and_then(|pad| pad.net)
+    }
+}
+
+/// The footprints of a board read from a `.kicad_pcb` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board<'a> {
+    pub footprints: Vec<BoardFootprint<'a>>,
+}
+
+impl<'a> Board<'a> {
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let sexpr = SExpr::try_from(input)?;
+        (&sexpr).try_into()
+    }
+
+    pub fn find_footprint(&self, ref_des: RefDes<'_>) -> Option<&BoardFootprint<'a>> {
+        self.footprints
+            .iter()
+            .find(|fp| fp.ref_des.as_str() == ref_des.as_str())
+    }
+}
+
+/// Find a text of a footprint, which is an `fp_text` before KiCad 8 and a `property` from then on
+fn footprint_text<'a>(value: &SExpr<'a>, kind: &str, property: &str) -> Option<&'a str> {
+    value
+        .children("fp_text")
+        .find_map(|text| {
+            let mut strings = text.strings();
+            (strings.next() == Some(kind)).then(|| strings.next())?
+        })
+        .or_else(|| {
+            value.children("property").find_map(|prop| {
+                let mut strings = prop.strings();
+                (strings.next() == Some(property)).then(|| strings.next())?
+            })
+        })
+}
+
+impl<'a> TryFrom<&SExpr<'a>> for BoardFootprint<'a> {
+    type Error = ParseError;
+
+    fn try_from(value: &SExpr<'a>) -> Result<Self, Self::Error> {
+        let footprint = value.string().ok_or(ParseError::MissingValue())?;
+        let ref_des = footprint_text(value, "reference", "Reference")
+            .ok_or_else(|| ParseError::MissingChild("reference".to_owned()))?;
+        let val = footprint_text(value, "value", "Value").unwrap_or_default();
+        let board_only = value
+            .child("attr")
+            .is_ok_and(|attr| attr.strings().any(|s| s == "board_only"));
+
+        let pads = value
+            .children("pad")
+            .map(|pad| {
+                let num = pad.string().ok_or(ParseError::MissingValue())?;
+                // Before KiCad 9 the net has both a number and a name
+                let net = pad
+                    .child("net")
+                    .ok()
+                    .and_then(|net| net.strings().last())
+                    .filter(|name| !name.is_empty());
+                Ok(BoardPad {
+                    num: num.into(),
+                    net: net.map(|name| name.into()),
+                })
+            })
+            .collect::<Result<_, ParseError>>()?;
+
+        Ok(BoardFootprint {
+            ref_des: ref_des.into(),
+            value: val.into(),
+            footprint: footprint.into(),
+            path: value.value("path").ok(),
+            board_only,
+            pads,
+        })
+    }
+}
+
+impl<'a> TryFrom<&SExpr<'a>> for Board<'a> {
+    type Error = ParseError;
+
+    fn try_from(value: &SExpr<'a>) -> Result<Self, Self::Error> {
+        let label = value.label().unwrap_or_default();
+        if label != "kicad_pcb" {
+            return Err(ParseError::UnexpectedRootLabel(label.to_owned()));
+        }
+
+        // Files written before KiCad 6 use `module` instead of `footprint`
+        let footprints = value
+            .children("footprint")
+            .chain(value.children("module"))
+            .map(|fp| fp.try_into())
+            .collect::<Result<_, _>>()?;
+        Ok(Board { footprints })
+    }
+}
+
+/// A difference between the netlist and the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardIssue<'a> {
+    /// A component with a footprint is not on the board
+    MissingFootprint(RefDes<'a>),
+    /// A footprint on the board has no component in the netlist
+    ExtraFootprint(RefDes<'a>),
+    /// The value on the board differs from the netlist
+    ValueMismatch {
+        ref_des: RefDes<'a>,
+        netlist: Value<'a>,
+        board: Value<'a>,
+    },
+    /// The footprint on the board differs from the one assigned in the netlist
+    FootprintMismatch {
+        ref_des: RefDes<'a>,
+        netlist: Option<Footprint<'a>>,
+        board: Footprint<'a>,
+    },
+    /// A pad is connected to a different net than its pin
+    WrongNet {
+        ref_des: RefDes<'a>,
+        num: PinNum<'a>,
+        netlist: Option<NetName<'a>>,
+        board: Option<NetName<'a>>,
+    },
+}
+
+impl Display for BoardIssue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let net =
+            |net: &Option<NetName<'_>>| net.map_or("no net".to_owned(), |net| net.to_string());
+        match self {
+            BoardIssue::MissingFootprint(ref_des) => write!(f, "{ref_des} is not on the board"),
+            BoardIssue::ExtraFootprint(ref_des) => {
+                write!(f, "{ref_des} is on the board but not in the netlist")
+            }
+            BoardIssue::ValueMismatch {
+                ref_des,
+                netlist,
+                board,
+            } => write!(
+                f,
+                "{ref_des} has value {board} on the board, expected {netlist}"
+            ),
+            BoardIssue::FootprintMismatch {
+                ref_des,
+                netlist,
+                board,
+            } => match netlist {
+                Some(netlist) => write!(
+                    f,
+                    "{ref_des} has footprint {board} on the board, expected {netlist}"
+                ),
+                None => write!(
+                    f,
+                    "{ref_des} has footprint {board} on the board but none in the netlist"
+                ),
+            },
+            BoardIssue::WrongNet {
+                ref_des,
+                num,
+                netlist,
+                board,
+            } => write!(
+                f,
+                "Pad {ref_des}.{num} is on {} but should be on {}",
+                net(board),
+                net(netlist)
+            ),
+        }
+    }
+}
+
+/// Compare the components and nets of the netlist with the footprints on the board
+///
+/// Pads without a number are ignored, and pins without a pad are left to
+/// [`check_pads`](crate::library::check_pads).
+pub fn compare<'a>(netlist: &NetList<'a>, board: &Board<'a>) -> Vec<BoardIssue<'a>> {
+    let netlist = &board_netlist(netlist);
+    let mut issues = vec![];
+    for comp in netlist.components.iter() {
+        let ref_des = comp.ref_des;
+        let Some(fp) = board.find_footprint(ref_des) else {
+            if comp.footprint.is_some() {
+                issues.push(BoardIssue::MissingFootprint(ref_des));
+            }
+            continue;
+        };
+
+        if fp.value != comp.value {
+            issues.push(BoardIssue::ValueMismatch {
+                ref_des,
+                netlist: comp.value,
+                board: fp.value,
+            });
+        }
+        if comp.footprint != Some(fp.footprint) {
+            issues.push(BoardIssue::FootprintMismatch {
+                ref_des,
+                netlist: comp.footprint,
+                board: fp.footprint,
+            });
+        }
+
+        let mut seen: Vec<PinNum<'a>> = vec![];
+        for pad in fp.pads.iter() {
+            if pad.num.as_str().is_empty() || seen.contains(&pad.num) {
+                continue;
+            }
+            seen.push(pad.num);
+            let expected = comp.find_pin(pad.num).map(|pin| pin.net);
+            if expected != pad.net {
+                issues.push(BoardIssue::WrongNet {
+                    ref_des,
+                    num: pad.num,
+                    netlist: expected,
+                    board: pad.net,
+                });
+            }
+        }
+    }
+
+    for fp in board.footprints.iter() {
+        if !fp.board_only && netlist.find_component(fp.ref_des).is_none() {
+            issues.push(BoardIssue::ExtraFootprint(fp.ref_des));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::component_mut;
+    use crate::Property;
+
+    #[test]
+    fn can_parse_board() {
+        let input = test_data!("kvt.kicad_pcb");
+        let board = Board::parse(&input).unwrap();
+
+        assert_eq!(board.footprints.len(), 4);
+        let u1 = board.find_footprint("U1".into()).unwrap();
+        assert_eq!(u1.value, "74LVC1G00".into());
+        assert_eq!(
+            u1.footprint,
+            "Package_TO_SOT_SMD:SOT-23-5_HandSoldering".into()
+        );
+        assert_eq!(u1.path, Some("/504a4355-a118-43c9-a7b5-0dbe2c3c67da"));
+        assert_eq!(u1.pad_net("4".into()), Some("Net-(U1-Out)".into()));
+
+        let h1 = board.find_footprint("H1".into()).unwrap();
+        assert!(h1.board_only);
+        assert_eq!(h1.pads[0].net, None);
+    }
+
+    #[test]
+    fn can_parse_kicad8_properties() {
+        let input = r#"(kicad_pcb (version 20240108) (generator "pcbnew")
+            (net 0 "") (net 1 "VCC")
+            (footprint "Resistor_SMD:R_0603_1608Metric" (layer "F.Cu")
+              (property "Reference" "R1" (at 0 -1.4 0) (layer "F.SilkS"))
+              (property "Value" "10k" (at 0 1.4 0) (layer "F.Fab"))
+              (pad "1" smd roundrect (at -0.8 0) (size 0.8 0.9) (layers "F.Cu") (net 1 "VCC"))
+              (pad "2" smd roundrect (at 0.8 0) (size 0.8 0.9) (layers "F.Cu"))))"#;
+        let board = Board::parse(input).unwrap();
+
+        let r1 = &board.footprints[0];
+        assert_eq!(r1.ref_des, "R1".into());
+        assert_eq!(r1.value, "10k".into());
+        assert_eq!(r1.pad_net("1".into()), Some("VCC".into()));
+        assert_eq!(r1.pad_net("2".into()), None);
+    }
+
+    #[test]
+    fn kvt_board_matches() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let input = test_data!("kvt.kicad_pcb");
+        let board = Board::parse(&input).unwrap();
+
+        assert_eq!(compare(&netlist, &board), vec![]);
+    }
+
+    #[test]
+    fn excluded_components_are_not_expected() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        component_mut(&mut netlist, "U2").properties.push(Property {
+            name: "exclude_from_board",
+            value: "",
+        });
+        let input = test_data!("kvt.kicad_pcb");
+        let mut board = Board::parse(&input).unwrap();
+        board.footprints.retain(|fp| fp.ref_des != "U2".into());
+
+        assert_eq!(compare(&netlist, &board), vec![]);
+    }
+
+    fn footprint_mut<'b, 'a>(
+        board: &'b mut Board<'a>,
+        ref_des: &str,
+    ) -> &'b mut BoardFootprint<'a> {
+        board
+            .footprints
+            .iter_mut()
+            .find(|fp| fp.ref_des == ref_des.into())
+            .unwrap()
+    }
+
+    #[test]
+    fn finds_differences() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let input = test_data!("kvt.kicad_pcb");
+        let mut board = Board::parse(&input).unwrap();
+        footprint_mut(&mut board, "U2").ref_des = "U3".into();
+        footprint_mut(&mut board, "U1").value = "74LVC1G02".into();
+        let j1 = footprint_mut(&mut board, "J1");
+        let pad = j1.pads.iter_mut().find(|pad| pad.num == "2".into());
+        pad.unwrap().net = Some("/B".into());
+
+        let issues = compare(&netlist, &board);
+        assert_eq!(
+            issues,
+            vec![
+                BoardIssue::WrongNet {
+                    ref_des: "J1".into(),
+                    num: "2".into(),
+                    netlist: Some("/A".into()),
+                    board: Some("/B".into()),
+                },
+                BoardIssue::ValueMismatch {
+                    ref_des: "U1".into(),
+                    netlist: "74LVC1G00".into(),
+                    board: "74LVC1G02".into(),
+                },
+                BoardIssue::MissingFootprint("U2".into()),
+                BoardIssue::ExtraFootprint("U3".into()),
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "Pad J1.2 is on /B but should be on /A"
+        );
+    }
+}
@@ -0,0 +1,427 @@
+//! Bill of materials
+//!
+//! Components are grouped by a configurable key, and each group becomes one line of the BOM. Components marked as
+//! do not populate or excluded from the BOM are left out unless asked for.
+
+use std::fmt::Write;
+
+use crate::quantity::Quantity;
+use crate::{Component, NetList, RefDes};
+
+/// What components are grouped by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupBy {
+    Value,
+    /// The parsed value, so `100nF` and `0.1u` on capacitors are grouped together
+    NormalizedValue,
+    Footprint,
+    PartId,
+    /// A user field or property, such as `MPN`
+    Field(String),
+}
+
+/// A column of the BOM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    References,
+    Quantity,
+    Value,
+    /// The parsed value in a common notation, such as `100nF`
+    NormalizedValue,
+    Footprint,
+    PartId,
+    Description,
+    /// A user field or property, such as `MPN`
+    Field(String),
+}
+
+impl Column {
+    fn header(&self) -> &str {
+        match self {
+            Column::References => "References",
+            Column::Quantity => "Qty",
+            Column::Value => "Value",
+            Column::NormalizedValue => "Normalized Value",
+            Column::Footprint => "Footprint",
+            Column::PartId => "Part",
+            Column::Description => "Description",
+            Column::Field(name) => name,
+        }
+    }
+}
+
+/// How the BOM is built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BomConfig {
+    pub group_by: Vec<GroupBy>,
+    pub columns: Vec<Column>,
+    /// Include components marked as do not populate or excluded from the BOM
+    pub include_dnp: bool,
+}
+
+impl Default for BomConfig {
+    fn default() -> Self {
+        Self {
+            group_by: vec![GroupBy::PartId, GroupBy::Value, GroupBy::Footprint],
+            columns: vec![
+                Column::References,
+                Column::Quantity,
+                Column::Value,
+                Column::Footprint,
+            ],
+            include_dnp: false,
+        }
+    }
+}
+
+/// A line of the BOM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BomLine<'a> {
+    /// The components on the line, in natural order
+    pub ref_des: Vec<RefDes<'a>>,
+    /// The text of each column
+    pub cells: Vec<String>,
+}
+
+/// A bill of materials
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bom<'a> {
+    pub title: Option<&'a str>,
+    pub rev: Option<&'a str>,
+    pub headers: Vec<String>,
+    pub lines: Vec<BomLine<'a>>,
+}
+
+fn field<'a>(comp: &Component<'a>, name: &str) -> Option<&'a str> {
+    comp.find_field(name).or_else(|| comp.find_property(name))
+}
+
+fn is_dnp(comp: &Component<'_>) -> bool {
+    comp.find_property("dnp").is_some() || comp.find_property("exclude_from_bom").is_some()
+}
+
+/// The parsed value and unit of a component, or the value text if it is not a number
+///
+/// Ratings such as the voltage are left out, so `100nF` and `0.1u/16V` on capacitors have the same value.
+fn normalized_value(comp: &Component<'_>) -> String {
+    Quantity::of(comp).map_or_else(|| comp.value.to_string(), |q| q.normalized())
+}
+
+fn group_value(comp: &Component<'_>, key: &GroupBy) -> String {
+    match key {
+        GroupBy::Value => comp.value.to_string(),
+        GroupBy::NormalizedValue => normalized_value(comp),
+        GroupBy::Footprint => comp.footprint.map(|f| f.to_string()).unwrap_or_default(),
+        GroupBy::PartId => format!("{}:{}", comp.part_id.lib, comp.part_id.part),
+        GroupBy::Field(name) => field(comp, name).unwrap_or_default().to_owned(),
+    }
+}
+
+/// Format a sorted list of reference designators, collapsing runs of three or more into ranges like `R1-R4`
+pub fn collapse_ranges(ref_des: &[RefDes<'_>]) -> String {
+    let mut parts: Vec<String> = vec![];
+    let mut run: Vec<RefDes<'_>> = vec![];
+
+    let flush = |run: &mut Vec<RefDes<'_>>, parts: &mut Vec<String>| {
+        if run.len() >= 3 {
+            parts.push(format!("{}-{}", run[0], run[run.len() - 1]));
+        } else {
+            parts.extend(run.iter().map(|s| s.to_string()));
+        }
+        run.clear();
+    };
+
+    for &r in ref_des.iter() {
+        let continues = run.last().is_some_and(|last| {
+            r.prefix() == last.prefix()
+                && matches!((last.number(), r.number()), (Some(a), Some(b)) if b == a + 1)
+        });
+        if !continues {
+            flush(&mut run, &mut parts);
+        }
+        run.push(r);
+    }
+    flush(&mut run, &mut parts);
+
+    parts.join(", ")
+}
+
+impl<'a> Bom<'a> {
+    pub fn new(netlist: &NetList<'a>, config: &BomConfig) -> Self {
+        let mut groups: Vec<(Vec<String>, Vec<&Component<'a>>)> = vec![];
+
+        for comp in netlist.components.iter() {
+            if !config.include_dnp && is_dnp(comp) {
+                continue;
+            }
+            let key: Vec<String> = config
+                .group_by
+                .iter()
+                .map(|key| group_value(comp, key))
+                .collect();
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, comps)) => comps.push(comp),
+                None => groups.push((key, vec![comp])),
+            }
+        }
+
+        let mut lines: Vec<BomLine<'a>> = groups
+            .into_iter()
+            .map(|(_, mut comps)| {
+                comps.sort_by_key(|comp| comp.ref_des);
+                let ref_des: Vec<RefDes<'a>> = comps.iter().map(|comp| comp.ref_des).collect();
+                let cells = config
+                    .columns
+                    .iter()
+                    .map(|column| cell(netlist, &comps, &ref_des, column))
+                    .collect();
+                BomLine { ref_des, cells }
+            })
+            .collect();
+
+        lines.sort_by_key(|line| line.ref_des[0]);
+
+        Bom {
+            title: netlist.design.title,
+            rev: netlist.design.rev,
+            headers: config
+                .columns
+                .iter()
+                .map(|column| column.header().to_owned())
+                .collect(),
+            lines,
+        }
+    }
+
+    fn heading(&self) -> Option<String> {
+        match (self.title, self.rev) {
+            (Some(title), Some(rev)) => Some(format!("{title} (rev {rev})")),
+            (Some(title), None) => Some(title.to_owned()),
+            (None, Some(rev)) => Some(format!("Rev {rev}")),
+            (None, None) => None,
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        fn quote(s: &str) -> String {
+            if s.contains([',', '"', '\n']) {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.to_owned()
+            }
+        }
+        let row = |cells: &[String]| {
+            cells
+                .iter()
+                .map(|cell| quote(cell))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let mut out = String::new();
+        if let Some(title) = self.title {
+            writeln!(out, "Title,{}", quote(title)).unwrap();
+        }
+        if let Some(rev) = self.rev {
+            writeln!(out, "Revision,{}", quote(rev)).unwrap();
+        }
+        if self.title.is_some() || self.rev.is_some() {
+            writeln!(out).unwrap();
+        }
+        writeln!(out, "{}", row(&self.headers)).unwrap();
+        for line in self.lines.iter() {
+            writeln!(out, "{}", row(&line.cells)).unwrap();
+        }
+        out
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let escape = |s: &str| s.replace('|', "\\|");
+        let row = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|cell| escape(cell)).collect();
+            format!("| {} |", cells.join(" | "))
+        };
+
+        let mut out = String::new();
+        if let Some(heading) = self.heading() {
+            writeln!(out, "# {}\n", escape(&heading)).unwrap();
+        }
+        writeln!(out, "{}", row(&self.headers)).unwrap();
+        writeln!(out, "|{}", " --- |".repeat(self.headers.len())).unwrap();
+        for line in self.lines.iter() {
+            writeln!(out, "{}", row(&line.cells)).unwrap();
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        fn escape(s: &str) -> String {
+            s.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        }
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"
+        )
+        .unwrap();
+        if let Some(heading) = self.heading() {
+            writeln!(out, "<title>{}</title>", escape(&heading)).unwrap();
+        }
+        writeln!(out, "</head>\n<body>").unwrap();
+        if let Some(heading) = self.heading() {
+            writeln!(out, "<h1>{}</h1>", escape(&heading)).unwrap();
+        }
+        writeln!(out, "<table>").unwrap();
+        write!(out, "<tr>").unwrap();
+        for header in self.headers.iter() {
+            write!(out, "<th>{}</th>", escape(header)).unwrap();
+        }
+        writeln!(out, "</tr>").unwrap();
+        for line in self.lines.iter() {
+            write!(out, "<tr>").unwrap();
+            for cell in line.cells.iter() {
+                write!(out, "<td>{}</td>", escape(cell)).unwrap();
+            }
+            writeln!(out, "</tr>").unwrap();
+        }
+        writeln!(out, "</table>\n</body>\n</html>").unwrap();
+        out
+    }
+}
+
+/// The text of a column, listing all distinct values if the components on the line differ
+fn cell(
+    netlist: &NetList<'_>,
+    comps: &[&Component<'_>],
+    ref_des: &[RefDes<'_>],
+    column: &Column,
+) -> String {
+    let value = |comp: &Component<'_>| -> String {
+        match column {
+            Column::Value => comp.value.to_string(),
+            Column::NormalizedValue => normalized_value(comp),
+            Column::Footprint => comp.footprint.map(|f| f.to_string()).unwrap_or_default(),
+            Column::PartId => format!("{}:{}", comp.part_id.lib, comp.part_id.part),
+            Column::Description => netlist
+                .find_part(comp.part_id)
+                .map(|part| part.description.to_string())
+                .unwrap_or_default(),
+            Column::Field(name) => field(comp, name).unwrap_or_default().to_owned(),
+            Column::References | Column::Quantity => unreachable!(),
+        }
+    };
+
+    match column {
+        Column::References => collapse_ranges(ref_des),
+        Column::Quantity => comps.len().to_string(),
+        _ => {
+            let mut values: Vec<String> = vec![];
+            for comp in comps {
+                let v = value(comp);
+                if !values.contains(&v) {
+                    values.push(v);
+                }
+            }
+            values.join(", ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::component_mut;
+    use crate::Property;
+
+    #[test]
+    fn collapse_ranges_works() {
+        let refs: Vec<RefDes> = ["C1", "R1", "R2", "R3", "R4", "R6", "R7", "R10", "U1A"]
+            .into_iter()
+            .map(RefDes::from)
+            .collect();
+        assert_eq!(collapse_ranges(&refs), "C1, R1-R4, R6, R7, R10, U1A");
+    }
+
+    #[test]
+    fn can_build_bom() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        netlist.design.title = Some("KVT");
+        let config = BomConfig {
+            columns: vec![
+                Column::References,
+                Column::Quantity,
+                Column::Value,
+                Column::Field("VerilogInclude".to_owned()),
+            ],
+            ..Default::default()
+        };
+        let bom = Bom::new(&netlist, &config);
+
+        assert_eq!(
+            bom.to_csv(),
+            "\
+Title,KVT
+
+References,Qty,Value,VerilogInclude
+J1,1,Conn_01x06_Pin,
+R1,1,R,
+\"U1, U2\",2,74LVC1G00,ttl.v
+"
+        );
+
+        assert!(bom.to_markdown().starts_with(
+            "# KVT\n\n| References | Qty | Value | VerilogInclude |\n| --- | --- | --- | --- |\n"
+        ));
+        assert!(bom
+            .to_html()
+            .contains("<tr><td>U1, U2</td><td>2</td><td>74LVC1G00</td><td>ttl.v</td></tr>"));
+    }
+
+    #[test]
+    fn dnp_components_are_excluded() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        component_mut(&mut netlist, "R1").properties.push(Property {
+            name: "dnp",
+            value: "",
+        });
+
+        let bom = Bom::new(&netlist, &BomConfig::default());
+        assert_eq!(bom.lines.len(), 2);
+
+        let config = BomConfig {
+            include_dnp: true,
+            ..Default::default()
+        };
+        let bom = Bom::new(&netlist, &config);
+        assert_eq!(bom.lines.len(), 3);
+    }
+
+    #[test]
+    fn can_group_by_normalized_value() {
+        let input = test_data!("analysis.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let config = BomConfig {
+            group_by: vec![GroupBy::NormalizedValue],
+            columns: vec![Column::References, Column::NormalizedValue, Column::Value],
+            ..Default::default()
+        };
+        let bom = Bom::new(&netlist, &config);
+
+        assert_eq!(bom.headers, vec!["References", "Normalized Value", "Value"]);
+        assert_eq!(
+            bom.lines[0].cells,
+            vec![
+                "C1, C4".to_owned(),
+                "100nF".to_owned(),
+                "100n, 0.1u/16V".to_owned()
+            ]
+        );
+        assert_eq!(bom.lines[1].cells[1], "4.7µF");
+    }
+}
@@ -0,0 +1,427 @@
+//! Build a netlist in code rather than parsing it
+//!
+//! Parts, components, nets and connections are collected first, and [`NetListBuilder::build`] checks them against each
+//! other the same way parsing does, filling in the component pins, the components of each part and the net nodes.
+//!
+//! ```
+//! use kicad_netlist::builder::NetListBuilder;
+//! use kicad_netlist::{PartId, PinType};
+//!
+//! let resistor = PartId { lib: "Device", part: "R" };
+//! let mut builder = NetListBuilder::new();
+//! builder
+//!     .part(resistor, "Resistor", &[("1", "~", PinType::Passive), ("2", "~", PinType::Passive)])
+//!     .component("R1", "10k", resistor)
+//!     .net("1", "VCC")
+//!     .net("2", "/EN")
+//!     .connect("R1", "1", "VCC")
+//!     .connect("R1", "2", "/EN");
+//! let netlist = builder.build().unwrap();
+//! assert_eq!(netlist.nets.len(), 2);
+//! ```
+
+use crate::{
+    BuildError, Component, ComponentPin, Design, Net, NetCode, NetList, NetName, NetNode, Part,
+    PartDescription, PartId, PartPin, PinNum, PinType, RefDes, Value,
+};
+
+/// Collects the contents of a netlist
+#[derive(Debug, Clone, Default)]
+pub struct NetListBuilder<'a> {
+    design: Design<'a>,
+    parts: Vec<Part<'a>>,
+    components: Vec<Component<'a>>,
+    nets: Vec<(NetCode<'a>, NetName<'a>)>,
+    connections: Vec<(RefDes<'a>, PinNum<'a>, NetName<'a>)>,
+}
+
+fn part_name(part_id: PartId<'_>) -> String {
+    format!("{}/{}", part_id.lib, part_id.part)
+}
+
+impl<'a> NetListBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn design(&mut self, design: Design<'a>) -> &mut Self {
+        self.design = design;
+        self
+    }
+
+    /// Add a part with pins given as number, name and type
+    pub fn part(
+        &mut self,
+        part_id: PartId<'a>,
+        description: &'a str,
+        pins: &[(&'a str, &'a str, PinType)],
+    ) -> &mut Self {
+        self.parts.push(Part {
+            part_id,
+            description: PartDescription(description),
+            footprint_filters: vec![],
+            pins: pins
+                .iter()
+                .map(|&(num, name, typ)| PartPin {
+                    num: num.into(),
+                    name: name.into(),
+                    typ,
+                })
+                .collect(),
+            components: vec![],
+        });
+        self
+    }
+
+    /// Add an instance of a part
+    pub fn component(
+        &mut self,
+        ref_des: &'a str,
+        value: &'a str,
+        part_id: PartId<'a>,
+    ) -> &mut Self {
+        self.add_component(Component {
+            ref_des: ref_des.into(),
+            value: Value(value),
+            part_id,
+            properties: vec![],
+            fields: vec![],
+            footprint: None,
+            pins: vec![],
+            sheet_path: None,
+            tstamp: None,
+        })
+    }
+
+    /// Add a component with its footprint, fields and other details filled in
+    ///
+    /// The pins are replaced by those of the part when building.
+    pub fn add_component(&mut self, component: Component<'a>) -> &mut Self {
+        self.components.push(component);
+        self
+    }
+
+    /// Add a net with its code and name
+    pub fn net(&mut self, code: &'a str, name: &'a str) -> &mut Self {
+        self.nets.push((NetCode(code), NetName(name)));
+        self
+    }
+
+    /// Connect a pin of a component to a net
+    pub fn connect(&mut self, ref_des: &'a str, num: &'a str, net: &'a str) -> &mut Self {
+        self.connections
+            .push((ref_des.into(), num.into(), NetName(net)));
+        self
+    }
+
+    /// Check the netlist and fill in the pins of every component, the components of every part and the net nodes
+    pub fn build(&self) -> Result<NetList<'a>, BuildError> {
+        for (i, part) in self.parts.iter().enumerate() {
+            if self.parts[..i].iter().any(|p| p.part_id == part.part_id) {
+                return Err(BuildError::DuplicatePart(part_name(part.part_id)));
+            }
+        }
+        for (i, comp) in self.components.iter().enumerate() {
+            if self.components[..i]
+                .iter()
+                .any(|c| c.ref_des == comp.ref_des)
+            {
+                return Err(BuildError::DuplicateComponent(comp.ref_des.to_string()));
+            }
+        }
+        for (i, (code, name)) in self.nets.iter().enumerate() {
+            if self.nets[..i].iter().any(|(c, n)| c == code || n == name) {
+                return Err(BuildError::DuplicateNet(name.to_string()));
+            }
+        }
+
+        let find_part = |part_id: PartId<'_>| self.parts.iter().find(|p| p.part_id == part_id);
+        let mut connections: Vec<(RefDes<'a>, PinNum<'a>, NetName<'a>, &PartPin<'a>)> = vec![];
+        for &(ref_des, num, net) in self.connections.iter() {
+            if !self.nets.iter().any(|(_, name)| *name == net) {
+                return Err(BuildError::UnknownNet(net.to_string()));
+            }
+            let comp = self
+                .components
+                .iter()
+                .find(|comp| comp.ref_des == ref_des)
+                .ok_or_else(|| BuildError::MissingComponent(ref_des.to_string()))?;
+            let pin = find_part(comp.part_id)
+                .ok_or_else(|| BuildError::MissingPart(part_name(comp.part_id)))?
+                .pins
+                .iter()
+                .find(|pin| pin.num == num)
+                .ok_or_else(|| BuildError::UnknownPin(ref_des.to_string(), num.to_string()))?;
+            match connections
+                .iter()
+                .find(|(r, n, _, _)| *r == ref_des && *n == num)
+            {
+                Some((_, _, other, _)) if *other == net => {}
+                Some((_, _, other, _)) => {
+                    return Err(BuildError::ConflictingNets(
+                        ref_des.to_string(),
+                        num.to_string(),
+                        other.to_string(),
+                        net.to_string(),
+                    ))
+                }
+                None => connections.push((ref_des, num, net, pin)),
+            }
+        }
+
+        let mut components = self.components.clone();
+        for comp in components.iter_mut() {
+            let part = find_part(comp.part_id)
+                .ok_or_else(|| BuildError::MissingPart(part_name(comp.part_id)))?;
+            comp.pins = part
+                .pins
+                .iter()
+                .map(|pin| {
+                    let net = connections
+                        .iter()
+                        .find(|(r, n, _, _)| *r == comp.ref_des && *n == pin.num)
+                        .map(|(_, _, net, _)| *net)
+                        .ok_or_else(|| {
+                            BuildError::MissingNet(comp.ref_des.to_string(), pin.num.to_string())
+                        })?;
+                    Ok(ComponentPin {
+                        num: pin.num,
+                        name: pin.name,
+                        typ: pin.typ,
+                        net,
+                    })
+                })
+                .collect::<Result<_, BuildError>>()?;
+        }
+
+        let mut parts = self.parts.clone();
+        for part in parts.iter_mut() {
+            part.components = components
+                .iter()
+                .filter(|comp| comp.part_id == part.part_id)
+                .map(|comp| comp.ref_des)
+                .collect();
+            if part.components.is_empty() {
+                return Err(BuildError::UnusedPart(part_name(part.part_id)));
+            }
+        }
+
+        let nets = self
+            .nets
+            .iter()
+            .map(|&(code, name)| {
+                let mut nodes: Vec<NetNode<'a>> = connections
+                    .iter()
+                    .filter(|(_, _, net, _)| *net == name)
+                    .map(|(ref_des, num, _, pin)| NetNode {
+                        ref_des: *ref_des,
+                        num: *num,
+                        function: match pin.name.as_str() {
+                            "" | "~" => None,
+                            name => Some(name.into()),
+                        },
+                        typ: pin.typ,
+                    })
+                    .collect();
+                nodes.sort_by_key(|node| (node.ref_des, node.num));
+                Net { code, name, nodes }
+            })
+            .collect();
+
+        Ok(NetList {
+            design: self.design,
+            components,
+            parts,
+            nets,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GATE: PartId = PartId {
+        lib: "74xGxx",
+        part: "74LVC1G04",
+    };
+    const RESISTOR: PartId = PartId {
+        lib: "Device",
+        part: "R",
+    };
+
+    fn builder() -> NetListBuilder<'static> {
+        let mut builder = NetListBuilder::new();
+        builder
+            .part(
+                GATE,
+                "Single NOT Gate",
+                &[
+                    ("2", "A", PinType::Input),
+                    ("3", "GND", PinType::PowerInput),
+                    ("4", "Y", PinType::Output),
+                    ("5", "VCC", PinType::PowerInput),
+                ],
+            )
+            .part(
+                RESISTOR,
+                "Resistor",
+                &[("1", "~", PinType::Passive), ("2", "~", PinType::Passive)],
+            )
+            .component("U1", "74LVC1G04", GATE)
+            .component("R1", "10k", RESISTOR)
+            .net("1", "/IN")
+            .net("2", "GND")
+            .net("3", "/OUT")
+            .net("4", "VCC")
+            .connect("U1", "2", "/IN")
+            .connect("U1", "3", "GND")
+            .connect("U1", "4", "/OUT")
+            .connect("U1", "5", "VCC")
+            .connect("R1", "1", "VCC")
+            .connect("R1", "2", "/IN");
+        builder
+    }
+
+    #[test]
+    fn can_build() {
+        let builder = builder();
+        let netlist = builder.build().unwrap();
+
+        let u1 = netlist.find_component("U1".into()).unwrap();
+        assert_eq!(u1.pins.len(), 4);
+        assert_eq!(u1.find_pin("4".into()).unwrap().net, "/OUT".into());
+        assert_eq!(
+            netlist.find_part(RESISTOR).unwrap().components,
+            vec!["R1".into()]
+        );
+
+        let input = netlist.find_net("/IN".into()).unwrap();
+        assert_eq!(input.code, "1".into());
+        assert_eq!(
+            input.nodes,
+            vec![
+                NetNode {
+                    ref_des: "R1".into(),
+                    num: "2".into(),
+                    function: None,
+                    typ: PinType::Passive,
+                },
+                NetNode {
+                    ref_des: "U1".into(),
+                    num: "2".into(),
+                    function: Some("A".into()),
+                    typ: PinType::Input,
+                },
+            ]
+        );
+        assert_eq!(netlist.nets.len(), 4);
+    }
+
+    #[test]
+    fn finds_dangling_references() {
+        let mut b = builder();
+        b.connect("R2", "1", "GND");
+        assert_eq!(
+            b.build().unwrap_err(),
+            BuildError::MissingComponent("R2".to_owned())
+        );
+
+        let mut b = builder();
+        b.connect("R1", "1", "/EN");
+        assert_eq!(
+            b.build().unwrap_err(),
+            BuildError::UnknownNet("/EN".to_owned())
+        );
+
+        let mut b = builder();
+        b.net("5", "VCC");
+        assert_eq!(
+            b.build().unwrap_err(),
+            BuildError::DuplicateNet("VCC".to_owned())
+        );
+
+        let mut b = builder();
+        b.connect("R1", "3", "GND");
+        assert_eq!(
+            b.build().unwrap_err(),
+            BuildError::UnknownPin("R1".to_owned(), "3".to_owned())
+        );
+
+        let mut b = builder();
+        b.connect("R1", "1", "GND");
+        assert_eq!(
+            b.build().unwrap_err().to_string(),
+            "Pin 1 of component R1 is connected to both VCC and GND"
+        );
+
+        let mut b = builder();
+        b.component("U2", "74LVC1G04", GATE);
+        assert_eq!(
+            b.build().unwrap_err(),
+            BuildError::MissingNet("U2".to_owned(), "2".to_owned())
+        );
+
+        let mut b = builder();
+        b.component(
+            "C1",
+            "100n",
+            PartId {
+                lib: "Device",
+                part: "C",
+            },
+        );
+        assert_eq!(
+            b.build().unwrap_err(),
+            BuildError::MissingPart("Device/C".to_owned())
+        );
+
+        let mut b = builder();
+        b.part(
+            PartId {
+                lib: "Device",
+                part: "C",
+            },
+            "Capacitor",
+            &[],
+        );
+        assert_eq!(
+            b.build().unwrap_err(),
+            BuildError::UnusedPart("Device/C".to_owned())
+        );
+
+        let mut b = builder();
+        b.component("R1", "1k", RESISTOR);
+        assert_eq!(
+            b.build().unwrap_err(),
+            BuildError::DuplicateComponent("R1".to_owned())
+        );
+    }
+
+    /// A netlist of one resistor between two nets, built from borrowed names
+    fn divider<'a>(ref_des: &'a str, top: &'a str, bottom: &'a str) -> NetList<'a> {
+        NetListBuilder::new()
+            .part(
+                RESISTOR,
+                "Resistor",
+                &[("1", "~", PinType::Passive), ("2", "~", PinType::Passive)],
+            )
+            .component(ref_des, "10k", RESISTOR)
+            .net("1", top)
+            .net("2", bottom)
+            .connect(ref_des, "1", top)
+            .connect(ref_des, "2", bottom)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn netlists_outlive_the_builder() {
+        let netlist = divider("R2", "VCC", "/EN");
+        assert_eq!(netlist.find_net("/EN".into()).unwrap().code, "2".into());
+        assert_eq!(
+            netlist.find_component("R2".into()).unwrap().pins[0].net,
+            "VCC".into()
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Classify nets as ground, power rails or signals
+//!
+//! A net is classified by the first of these which applies:
+//!
+//! 1. The configured [`NetRule`]s, in order.
+//! 2. Ground names such as `GND`, `AGND` or `VSS`.
+//! 3. Power names such as `VCC`, `+3V3` or `-12V`.
+//! 4. A power output pin on the net, or a power input pin, which makes it a power rail.
+//!
+//! Everything else is a signal. Names are matched without their sheet path, so `/power/+5V` is a power rail.
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::{Net, NetList, NetName, PinType};
+
+/// The kind of a net
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetClass {
+    Ground,
+    Power,
+    Signal,
+}
+
+impl Display for NetClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetClass::Ground => write!(f, "ground"),
+            NetClass::Power => write!(f, "power"),
+            NetClass::Signal => write!(f, "signal"),
+        }
+    }
+}
+
+/// Classify nets whose name matches a regular expression
+#[derive(Debug, Clone)]
+pub struct NetRule {
+    pub pattern: Regex,
+    pub class: NetClass,
+}
+
+impl NetRule {
+    /// The pattern is matched against the full net name, including the sheet path
+    pub fn new(pattern: &str, class: NetClass) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: Regex::new(pattern)?,
+            class,
+        })
+    }
+}
+
+/// Settings for the classification
+#[derive(Debug, Clone)]
+pub struct ClassifyConfig {
+    /// Rules which are applied before anything else
+    pub rules: Vec<NetRule>,
+    /// Recognize common ground and power names
+    pub builtin_names: bool,
+    /// Treat nets with power pins as power rails
+    pub power_pins: bool,
+}
+
+impl Default for ClassifyConfig {
+    fn default() -> Self {
+        Self {
+            rules: vec![],
+            builtin_names: true,
+            power_pins: true,
+        }
+    }
+}
+
+/// The class of a net
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetInfo<'a> {
+    pub net: NetName<'a>,
+    pub class: NetClass,
+    /// The nominal voltage from the name of a rail, which is zero for ground
+    pub voltage: Option<f64>,
+}
+
+fn ground_name() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)^(?:[ADPS]?GND[ADP]?|GND_\w+|VSS[A-Z]?|0V|EARTH|CHASSIS)$").unwrap()
+    })
+}
+
+fn power_name() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)^(?:V(?:CC|DD|EE|BAT|BUS|IN|SYS|PP|IO)\w*|[+-]?\d+(?:V\d*|\.\d+V)(?:_\w+)?)$",
+        )
+        .unwrap()
+    })
+}
+
+fn voltage_pattern() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)(?:^|[^0-9A-Z.])([+-]?)(\d+)(?:V(\d*)|\.(\d+)V)(?:$|[^0-9A-Z])").unwrap()
+    })
+}
+
+/// Parse a voltage from a rail name, such as `+3V3`, `5V0`, `-12V`, `1.8V` or `VDD_1V2`
+pub fn parse_voltage(name: &str) -> Option<f64> {
+    let caps = voltage_pattern().captures(NetName::from(name).local_name())?;
+    let whole = caps.get(2)?.as_str();
+    let fraction = caps
+        .get(3)
+        .or_else(|| caps.get(4))
+        .map_or("", |m| m.as_str());
+    let voltage: f64 = format!("{whole}.{fraction}0").parse().ok()?;
+    Some(if &caps[1] == "-" { -voltage } else { voltage })
+}
+
+/// Classify a single net
+pub fn classify_net<'a>(net: &Net<'a>, config: &ClassifyConfig) -> NetInfo<'a> {
+    let name = net.name.as_str();
+    let local = net.name.local_name();
+
+    let by_rule = config
+        .rules
+        .iter()
+        .find(|rule| rule.pattern.is_match(name))
+        .map(|rule| rule.class);
+    let by_name = || {
+        if !config.builtin_names {
+            None
+        } else if ground_name().is_match(local) {
+            Some(NetClass::Ground)
+        } else if power_name().is_match(local) {
+            Some(NetClass::Power)
+        } else {
+            None
+        }
+    };
+    let by_pins = || {
+        let power = net
+            .nodes
+            .iter()
+            .any(|node| matches!(node.typ, PinType::PowerOutput | PinType::PowerInput));
+        (config.power_pins && power).then_some(NetClass::Power)
+    };
+    let class = by_rule
+        .or_else(by_name)
+        .or_else(by_pins)
+        .unwrap_or(NetClass::Signal);
+
+    let voltage = match class {
+        NetClass::Ground => Some(0.0),
+        NetClass::Power => parse_voltage(name),
+        NetClass::Signal => None,
+    };
+    NetInfo {
+        net: net.name,
+        class,
+        voltage,
+    }
+}
+
+/// Classify every net of the netlist
+pub fn classify<'a>(netlist: &NetList<'a>, config: &ClassifyConfig) -> Vec<NetInfo<'a>> {
+    netlist
+        .nets
+        .iter()
+        .map(|net| classify_net(net, config))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("+3V3", Some(3.3))]
+    #[case("/power/+5V", Some(5.0))]
+    #[case("5V0", Some(5.0))]
+    #[case("-12V", Some(-12.0))]
+    #[case("+1.8V", Some(1.8))]
+    #[case("VDD_1V2", Some(1.2))]
+    #[case("VCC", None)]
+    #[case("GPIO5", None)]
+    #[case("R5V", None)]
+    fn can_parse_voltage(#[case] name: &str, #[case] voltage: Option<f64>) {
+        assert_eq!(parse_voltage(name), voltage);
+    }
+
+    #[rstest]
+    #[case("+3V3", NetClass::Power)]
+    #[case("-12V", NetClass::Power)]
+    #[case("+5V_USB", NetClass::Power)]
+    #[case("1.8V", NetClass::Power)]
+    #[case("VDDA", NetClass::Power)]
+    #[case("AGND", NetClass::Ground)]
+    #[case("-RESET", NetClass::Signal)]
+    #[case("+WR", NetClass::Signal)]
+    fn can_classify_by_name(#[case] name: &str, #[case] class: NetClass) {
+        let net = Net {
+            code: "1".into(),
+            name: name.into(),
+            nodes: vec![],
+        };
+        let info = classify_net(&net, &ClassifyConfig::default());
+        assert_eq!(info.class, class);
+    }
+
+    #[test]
+    fn kvt_nets() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let classes: Vec<_> = classify(&netlist, &ClassifyConfig::default())
+            .iter()
+            .map(|info| (info.net.as_str(), info.class))
+            .collect();
+        assert_eq!(
+            classes,
+            vec![
+                ("/A", NetClass::Signal),
+                ("/B", NetClass::Signal),
+                ("/OUT", NetClass::Signal),
+                ("GND", NetClass::Ground),
+                ("Net-(U1-Out)", NetClass::Signal),
+                ("Net-(U2-B)", NetClass::Signal),
+                ("VCC", NetClass::Power),
+            ]
+        );
+    }
+
+    #[test]
+    fn rules_come_first() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let config = ClassifyConfig {
+            rules: vec![
+                NetRule::new("^/OUT$", NetClass::Power).unwrap(),
+                NetRule::new("^VCC$", NetClass::Signal).unwrap(),
+            ],
+            ..Default::default()
+        };
+        let classes = classify(&netlist, &config);
+        assert_eq!(classes[2].class, NetClass::Power);
+        assert_eq!(classes[6].class, NetClass::Signal);
+
+        // Without names, the power pins still make GND a rail
+        let config = ClassifyConfig {
+            builtin_names: false,
+            ..Default::default()
+        };
+        let classes = classify(&netlist, &config);
+        assert_eq!(classes[3].class, NetClass::Power);
+        assert_eq!(classes[3].voltage, None);
+    }
+}
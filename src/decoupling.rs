@@ -0,0 +1,270 @@
+//! Check that every power rail of an IC is decoupled
+//!
+//! A decoupling capacitor connects a power rail directly to a ground net, as classified by
+//! [`classify`](crate::classify). Every component with a power input pin on a rail needs at least
+//! [`DecouplingConfig::min_capacitors`] of them on that rail. The netlist does not tell which capacitor sits next to
+//! which IC, so the capacitors of a rail count for every IC on it.
+
+use std::fmt::Display;
+
+use crate::classify::{classify, ClassifyConfig, NetClass};
+use crate::quantity::{Quantity, Unit};
+use crate::{Component, NetList, NetName, PinType, RefDes};
+
+/// A way to recognize capacitors
+#[derive(Debug, Clone)]
+pub enum CapacitorMatch {
+    /// The reference designator is the prefix followed by a number, such as `C12`
+    RefPrefix(String),
+    /// The component is an instance of the part
+    Part {
+        lib: String,
+        part: String,
+    },
+    Predicate(fn(&Component<'_>) -> bool),
+}
+
+impl CapacitorMatch {
+    pub fn matches(&self, comp: &Component<'_>) -> bool {
+        match self {
+            CapacitorMatch::RefPrefix(prefix) => {
+                let ref_des = comp.ref_des.as_str();
+                ref_des.trim_end_matches(|c: char| c.is_ascii_digit()) == prefix
+                    && ref_des.len() > prefix.len()
+            }
+            CapacitorMatch::Part { lib, part } => {
+                comp.part_id.lib == lib && comp.part_id.part == part
+            }
+            CapacitorMatch::Predicate(predicate) => predicate(comp),
+        }
+    }
+}
+
+/// Settings for the decoupling audit
+#[derive(Debug, Clone)]
+pub struct DecouplingConfig {
+    pub classify: ClassifyConfig,
+    /// Components matching any of these are capacitors
+    pub capacitors: Vec<CapacitorMatch>,
+    /// The number of capacitors each rail of an IC needs
+    pub min_capacitors: usize,
+}
+
+impl Default for DecouplingConfig {
+    fn default() -> Self {
+        Self {
+            classify: ClassifyConfig::default(),
+            capacitors: vec![CapacitorMatch::RefPrefix("C".to_owned())],
+            min_capacitors: 1,
+        }
+    }
+}
+
+/// The decoupling capacitors of a power rail
+#[derive(Debug, Clone, PartialEq)]
+pub struct RailDecoupling<'a> {
+    pub rail: NetName<'a>,
+    pub capacitors: Vec<RefDes<'a>>,
+    /// The total capacitance in farads of the capacitors with a value which could be parsed
+    pub capacitance: f64,
+    /// Capacitors with a value which could not be parsed
+    pub unparsed: Vec<RefDes<'a>>,
+}
+
+/// A problem found by the audit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecouplingIssue<'a> {
+    /// An IC has too few capacitors on one of its rails
+    Undecoupled {
+        ref_des: RefDes<'a>,
+        rail: NetName<'a>,
+        found: usize,
+        required: usize,
+    },
+    /// Both pins of a capacitor are on the same net
+    ShortedCapacitor {
+        ref_des: RefDes<'a>,
+        net: NetName<'a>,
+    },
+}
+
+impl Display for DecouplingIssue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecouplingIssue::Undecoupled {
+                ref_des,
+                rail,
+                found,
+                required,
+            } => write!(
+                f,
+                "{ref_des} has {found} decoupling capacitors on {rail}, expected at least {required}"
+            ),
+            DecouplingIssue::ShortedCapacitor { ref_des, net } => {
+                write!(f, "Both pins of {ref_des} are on {net}")
+            }
+        }
+    }
+}
+
+/// The result of the decoupling audit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecouplingReport<'a> {
+    pub rails: Vec<RailDecoupling<'a>>,
+    pub issues: Vec<DecouplingIssue<'a>>,
+}
+
+impl<'a> DecouplingReport<'a> {
+    pub fn find_rail(&self, rail: NetName<'_>) -> Option<&RailDecoupling<'a>> {
+        self.rails.iter().find(|r| r.rail.as_str() == rail.as_str())
+    }
+}
+
+/// Find the decoupling capacitors of every rail and the ICs lacking them
+pub fn audit<'a>(netlist: &NetList<'a>, config: &DecouplingConfig) -> DecouplingReport<'a> {
+    let classes = classify(netlist, &config.classify);
+    let class = |net: NetName<'_>| {
+        classes
+            .iter()
+            .find(|info| info.net.as_str() == net.as_str())
+            .map(|info| info.class)
+    };
+
+    let mut rails: Vec<RailDecoupling<'a>> = netlist
+        .nets
+        .iter()
+        .filter(|net| class(net.name) == Some(NetClass::Power))
+        .map(|net| RailDecoupling {
+            rail: net.name,
+            capacitors: vec![],
+            capacitance: 0.0,
+            unparsed: vec![],
+        })
+        .collect();
+
+    let mut issues = vec![];
+    let capacitors = netlist
+        .components
+        .iter()
+        .filter(|comp| config.capacitors.iter().any(|m| m.matches(comp)));
+    for comp in capacitors {
+        let mut nets: Vec<NetName<'a>> = comp.pins.iter().map(|pin| pin.net).collect();
+        nets.dedup();
+        match nets[..] {
+            [net] if comp.pins.len() > 1 => issues.push(DecouplingIssue::ShortedCapacitor {
+                ref_des: comp.ref_des,
+                net,
+            }),
+            [a, b] => {
+                let rail = match (class(a), class(b)) {
+                    (Some(NetClass::Power), Some(NetClass::Ground)) => a,
+                    (Some(NetClass::Ground), Some(NetClass::Power)) => b,
+                    _ => continue,
+                };
+                let Some(rail) = rails.iter_mut().find(|r| r.rail == rail) else {
+                    continue;
+                };
+                rail.capacitors.push(comp.ref_des);
+                match Quantity::of(comp).filter(|q| q.is(Unit::Farad)) {
+                    Some(quantity) => rail.capacitance += quantity.value,
+                    None => rail.unparsed.push(comp.ref_des),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for comp in netlist.components.iter() {
+        let mut checked: Vec<NetName<'a>> = vec![];
+        for pin in comp
+            .pins
+            .iter()
+            .filter(|pin| pin.typ == PinType::PowerInput)
+        {
+            if checked.contains(&pin.net) {
+                continue;
+            }
+            checked.push(pin.net);
+            let Some(rail) = rails.iter().find(|r| r.rail == pin.net) else {
+                continue;
+            };
+            if rail.capacitors.len() < config.min_capacitors {
+                issues.push(DecouplingIssue::Undecoupled {
+                    ref_des: comp.ref_des,
+                    rail: pin.net,
+                    found: rail.capacitors.len(),
+                    required: config.min_capacitors,
+                });
+            }
+        }
+    }
+
+    DecouplingReport { rails, issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_sum_capacitance() {
+        let input = test_data!("analysis.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let report = audit(&netlist, &DecouplingConfig::default());
+
+        let rail = report.find_rail("+3V3".into()).unwrap();
+        assert_eq!(rail.capacitors, vec!["C1".into(), "C2".into()]);
+        assert!((rail.capacitance - 4.8e-6).abs() < 1e-12);
+        assert_eq!(rail.unparsed, vec![]);
+        assert_eq!(
+            report.find_rail("+5V".into()).unwrap().capacitors,
+            vec!["C4".into()]
+        );
+        assert_eq!(
+            report.issues,
+            vec![DecouplingIssue::ShortedCapacitor {
+                ref_des: "C3".into(),
+                net: "GND".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn finds_undecoupled_ics() {
+        let input = test_data!("analysis.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let config = DecouplingConfig {
+            min_capacitors: 2,
+            ..Default::default()
+        };
+        let report = audit(&netlist, &config);
+
+        assert_eq!(
+            report.issues[1..],
+            vec![DecouplingIssue::Undecoupled {
+                ref_des: "U2".into(),
+                rail: "+5V".into(),
+                found: 1,
+                required: 2
+            }]
+        );
+        assert_eq!(
+            report.issues[1].to_string(),
+            "U2 has 1 decoupling capacitors on +5V, expected at least 2"
+        );
+
+        // Only C2 has a unit in its value, which leaves +5V without capacitors
+        let config = DecouplingConfig {
+            capacitors: vec![CapacitorMatch::Predicate(|comp| {
+                comp.value.as_str().ends_with('F')
+            })],
+            ..Default::default()
+        };
+        let report = audit(&netlist, &config);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(
+            report.find_rail("+3V3".into()).unwrap().capacitors,
+            vec!["C2".into()]
+        );
+    }
+}
@@ -0,0 +1,552 @@
+//! Differences between two revisions of a netlist
+//!
+//! Nets are matched by the pins they connect rather than by name. Only pins which exist in both netlists are used
+//! for matching, so adding or removing a component does not by itself change any net. A pin which is alone on its net
+//! counts as unconnected, and a pin of a component in both netlists which becomes connected or unconnected is
+//! reported on its own rather than as a change to any net.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Display;
+
+use crate::{Component, Footprint, Net, NetList, NetName, PartId, PinNum, Property, RefDes, Value};
+
+/// A change to a single component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentChange<'a> {
+    Value {
+        old: Value<'a>,
+        new: Value<'a>,
+    },
+    Footprint {
+        old: Option<Footprint<'a>>,
+        new: Option<Footprint<'a>>,
+    },
+    Part {
+        old: PartId<'a>,
+        new: PartId<'a>,
+    },
+    Property {
+        name: &'a str,
+        old: Option<&'a str>,
+        new: Option<&'a str>,
+    },
+    Field {
+        name: &'a str,
+        old: Option<&'a str>,
+        new: Option<&'a str>,
+    },
+}
+
+/// A pin which was moved from one net to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinMove<'a> {
+    pub ref_des: RefDes<'a>,
+    pub num: PinNum<'a>,
+    pub old: NetName<'a>,
+    pub new: NetName<'a>,
+}
+
+/// A pin which was connected to a net or disconnected from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinConnection<'a> {
+    pub ref_des: RefDes<'a>,
+    pub num: PinNum<'a>,
+    pub net: NetName<'a>,
+}
+
+/// Several old nets which were merged into one new net
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetMerge<'a> {
+    pub old: Vec<NetName<'a>>,
+    pub new: NetName<'a>,
+}
+
+/// One old net which was split into several new nets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetSplit<'a> {
+    pub old: NetName<'a>,
+    pub new: Vec<NetName<'a>>,
+}
+
+/// The differences between two netlists
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetListDiff<'a> {
+    pub added_components: Vec<RefDes<'a>>,
+    pub removed_components: Vec<RefDes<'a>>,
+    pub changed_components: Vec<(RefDes<'a>, Vec<ComponentChange<'a>>)>,
+    pub added_nets: Vec<NetName<'a>>,
+    pub removed_nets: Vec<NetName<'a>>,
+    /// Pairs of old and new names of nets connecting the same pins
+    pub renamed_nets: Vec<(NetName<'a>, NetName<'a>)>,
+    pub merged_nets: Vec<NetMerge<'a>>,
+    pub split_nets: Vec<NetSplit<'a>>,
+    pub moved_pins: Vec<PinMove<'a>>,
+    /// Pins which were unconnected in the old netlist, with their new net
+    pub connected_pins: Vec<PinConnection<'a>>,
+    /// Pins which are unconnected in the new netlist, with their old net
+    pub disconnected_pins: Vec<PinConnection<'a>>,
+}
+
+impl<'a> NetListDiff<'a> {
+    pub fn is_empty(&self) -> bool {
+        self == &NetListDiff::default()
+    }
+}
+
+fn component_changes<'a>(old: &Component<'a>, new: &Component<'a>) -> Vec<ComponentChange<'a>> {
+    let mut changes = vec![];
+
+    if old.value != new.value {
+        changes.push(ComponentChange::Value {
+            old: old.value,
+            new: new.value,
+        });
+    }
+    if old.footprint != new.footprint {
+        changes.push(ComponentChange::Footprint {
+            old: old.footprint,
+            new: new.footprint,
+        });
+    }
+    if old.part_id != new.part_id {
+        changes.push(ComponentChange::Part {
+            old: old.part_id,
+            new: new.part_id,
+        });
+    }
+
+    for name in names(&old.properties, &new.properties) {
+        let (old, new) = (old.find_property(name), new.find_property(name));
+        if old != new {
+            changes.push(ComponentChange::Property { name, old, new });
+        }
+    }
+    for name in names(&old.fields, &new.fields) {
+        let (old, new) = (old.find_field(name), new.find_field(name));
+        if old != new {
+            changes.push(ComponentChange::Field { name, old, new });
+        }
+    }
+
+    changes
+}
+
+/// The distinct names of two lists of properties, in order
+fn names<'a>(old: &[Property<'a>], new: &[Property<'a>]) -> Vec<&'a str> {
+    let mut names: Vec<&'a str> = vec![];
+    for prop in old.iter().chain(new.iter()) {
+        if !names.contains(&prop.name) {
+            names.push(prop.name);
+        }
+    }
+    names
+}
+
+type Pin<'a> = (RefDes<'a>, PinNum<'a>);
+
+/// Whether a net connects anything, rather than holding a single unconnected pin
+fn connects(net: &Net<'_>) -> bool {
+    net.nodes.len() > 1
+}
+
+/// The net of every connected pin
+fn pin_nets<'a>(netlist: &NetList<'a>) -> HashMap<Pin<'a>, NetName<'a>> {
+    netlist
+        .nets
+        .iter()
+        .filter(|net| connects(net))
+        .flat_map(|net| {
+            net.nodes
+                .iter()
+                .map(move |node| ((node.ref_des, node.num), net.name))
+        })
+        .collect()
+}
+
+/// The distinct names of the nets which connect anything, in order
+fn net_names<'a>(netlist: &NetList<'a>) -> Vec<NetName<'a>> {
+    let mut names: Vec<NetName<'a>> = vec![];
+    for net in netlist.nets.iter().filter(|net| connects(net)) {
+        if !names.contains(&net.name) {
+            names.push(net.name);
+        }
+    }
+    names
+}
+
+/// The partner with the largest overlap, preferring one with the same name
+fn dominant<'a>(name: NetName<'a>, partners: &BTreeMap<&'a str, usize>) -> Option<NetName<'a>> {
+    partners
+        .iter()
+        .max_by(|(a_name, a), (b_name, b)| {
+            a.cmp(b)
+                .then_with(|| (**a_name == name.as_str()).cmp(&(**b_name == name.as_str())))
+                .then_with(|| b_name.cmp(a_name))
+        })
+        .map(|(name, _)| NetName::from(*name))
+}
+
+/// Compare two netlists
+pub fn diff<'a>(old: &NetList<'a>, new: &NetList<'a>) -> NetListDiff<'a> {
+    let mut result = NetListDiff::default();
+
+    for comp in old.components.iter() {
+        match new.find_component(comp.ref_des) {
+            None => result.removed_components.push(comp.ref_des),
+            Some(new_comp) => {
+                let changes = component_changes(comp, new_comp);
+                if !changes.is_empty() {
+                    result.changed_components.push((comp.ref_des, changes));
+                }
+            }
+        }
+    }
+    for comp in new.components.iter() {
+        if old.find_component(comp.ref_des).is_none() {
+            result.added_components.push(comp.ref_des);
+        }
+    }
+
+    let old_nets = pin_nets(old);
+    let new_nets = pin_nets(new);
+
+    let old_names = net_names(old);
+    let new_names = net_names(new);
+
+    let mut common: Vec<(Pin<'a>, NetName<'a>, NetName<'a>)> = old_nets
+        .iter()
+        .filter_map(|(pin, old_net)| new_nets.get(pin).map(|new_net| (*pin, *old_net, *new_net)))
+        .collect();
+    common.sort_by_key(|(pin, _, _)| *pin);
+
+    // Pins of components in both netlists which are connected in only one of them
+    let in_both = |(ref_des, _): &Pin<'a>| {
+        old.find_component(*ref_des).is_some() && new.find_component(*ref_des).is_some()
+    };
+    let only_in = |nets: &HashMap<Pin<'a>, NetName<'a>>, other: &HashMap<Pin<'a>, NetName<'a>>| {
+        let mut pins: Vec<PinConnection<'a>> = nets
+            .iter()
+            .filter(|(pin, _)| in_both(pin) && !other.contains_key(*pin))
+            .map(|(&(ref_des, num), &net)| PinConnection { ref_des, num, net })
+            .collect();
+        pins.sort_by_key(|pin| (pin.ref_des, pin.num));
+        pins
+    };
+    result.connected_pins = only_in(&new_nets, &old_nets);
+    result.disconnected_pins = only_in(&old_nets, &new_nets);
+
+    let mut old_partners: HashMap<NetName<'a>, BTreeMap<&'a str, usize>> = HashMap::new();
+    let mut new_partners: HashMap<NetName<'a>, BTreeMap<&'a str, usize>> = HashMap::new();
+    for (_, old_net, new_net) in common.iter() {
+        *old_partners
+            .entry(*old_net)
+            .or_default()
+            .entry(new_net.as_str())
+            .or_default() += 1;
+        *new_partners
+            .entry(*new_net)
+            .or_default()
+            .entry(old_net.as_str())
+            .or_default() += 1;
+    }
+
+    let dominant_new: HashMap<NetName<'a>, NetName<'a>> = old_partners
+        .iter()
+        .filter_map(|(name, partners)| Some((*name, dominant(*name, partners)?)))
+        .collect();
+    let dominant_old: HashMap<NetName<'a>, NetName<'a>> = new_partners
+        .iter()
+        .filter_map(|(name, partners)| Some((*name, dominant(*name, partners)?)))
+        .collect();
+
+    for name in old_names.iter() {
+        if !old_partners.contains_key(name) {
+            result.removed_nets.push(*name);
+        }
+    }
+    for name in new_names.iter() {
+        if !new_partners.contains_key(name) {
+            result.added_nets.push(*name);
+        }
+    }
+
+    for new_net in new_names.iter().copied() {
+        let merged: Vec<NetName<'a>> = old_names
+            .iter()
+            .copied()
+            .filter(|old_net| dominant_new.get(old_net) == Some(&new_net))
+            .collect();
+        if merged.len() > 1 {
+            result.merged_nets.push(NetMerge {
+                old: merged,
+                new: new_net,
+            });
+        }
+    }
+
+    for old_net in old_names.iter().copied() {
+        let split: Vec<NetName<'a>> = new_names
+            .iter()
+            .copied()
+            .filter(|new_net| dominant_old.get(new_net) == Some(&old_net))
+            .collect();
+        if split.len() > 1 {
+            result.split_nets.push(NetSplit {
+                old: old_net,
+                new: split,
+            });
+        }
+    }
+
+    for old_net in old_names.iter().copied() {
+        let Some(new_net) = dominant_new.get(&old_net).copied() else {
+            continue;
+        };
+        let is_merged = result.merged_nets.iter().any(|m| m.new == new_net);
+        let is_split = result.split_nets.iter().any(|s| s.old == old_net);
+        if dominant_old.get(&new_net) == Some(&old_net)
+            && !is_merged
+            && !is_split
+            && old_net.as_str() != new_net.as_str()
+        {
+            result.renamed_nets.push((old_net, new_net));
+        }
+    }
+
+    for ((ref_des, num), old_net, new_net) in common {
+        if dominant_new.get(&old_net) != Some(&new_net)
+            && dominant_old.get(&new_net) != Some(&old_net)
+        {
+            result.moved_pins.push(PinMove {
+                ref_des,
+                num,
+                old: old_net,
+                new: new_net,
+            });
+        }
+    }
+
+    result
+}
+
+impl Display for ComponentChange<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn opt(s: Option<impl Display>) -> String {
+            s.map(|s| format!("\"{s}\"")).unwrap_or("(none)".to_owned())
+        }
+        match self {
+            ComponentChange::Value { old, new } => write!(f, "value \"{old}\" -> \"{new}\""),
+            ComponentChange::Footprint { old, new } => {
+                write!(f, "footprint {} -> {}", opt(*old), opt(*new))
+            }
+            ComponentChange::Part { old, new } => write!(
+                f,
+                "part {}:{} -> {}:{}",
+                old.lib, old.part, new.lib, new.part
+            ),
+            ComponentChange::Property { name, old, new } => {
+                write!(f, "property {name} {} -> {}", opt(*old), opt(*new))
+            }
+            ComponentChange::Field { name, old, new } => {
+                write!(f, "field {name} {} -> {}", opt(*old), opt(*new))
+            }
+        }
+    }
+}
+
+/// A human readable engineering change order
+impl Display for NetListDiff<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+
+        for ref_des in self.removed_components.iter() {
+            writeln!(f, "Remove component {ref_des}")?;
+        }
+        for ref_des in self.added_components.iter() {
+            writeln!(f, "Add component {ref_des}")?;
+        }
+        for (ref_des, changes) in self.changed_components.iter() {
+            for change in changes {
+                writeln!(f, "Change {ref_des} {change}")?;
+            }
+        }
+        for net in self.removed_nets.iter() {
+            writeln!(f, "Remove net {net}")?;
+        }
+        for net in self.added_nets.iter() {
+            writeln!(f, "Add net {net}")?;
+        }
+        for (old, new) in self.renamed_nets.iter() {
+            writeln!(f, "Rename net {old} -> {new}")?;
+        }
+        for merge in self.merged_nets.iter() {
+            let old: BTreeSet<&str> = merge.old.iter().map(|n| n.as_str()).collect();
+            let old: Vec<&str> = old.into_iter().collect();
+            writeln!(f, "Merge nets {} into {}", old.join(", "), merge.new)?;
+        }
+        for split in self.split_nets.iter() {
+            let new: Vec<&str> = split.new.iter().map(|n| n.as_str()).collect();
+            writeln!(f, "Split net {} into {}", split.old, new.join(", "))?;
+        }
+        for pin in self.moved_pins.iter() {
+            writeln!(
+                f,
+                "Move pin {}.{} from {} to {}",
+                pin.ref_des, pin.num, pin.old, pin.new
+            )?;
+        }
+        for pin in self.connected_pins.iter() {
+            writeln!(f, "Connect pin {}.{} to {}", pin.ref_des, pin.num, pin.net)?;
+        }
+        for pin in self.disconnected_pins.iter() {
+            writeln!(
+                f,
+                "Disconnect pin {}.{} from {}",
+                pin.ref_des, pin.num, pin.net
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{move_node, rename_net};
+
+    #[test]
+    fn identical_netlists_have_no_diff() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+
+        assert!(diff(&netlist, &netlist).is_empty());
+    }
+
+    #[test]
+    fn renamed_nets_are_detected() {
+        let input = test_data!("kvt.net");
+        let old = NetList::parse(&input).unwrap();
+        let mut new = old.clone();
+        rename_net(&mut new, "Net-(U1-Out)", "/MID");
+
+        let result = diff(&old, &new);
+        assert_eq!(
+            result.renamed_nets,
+            vec![(NetName::from("Net-(U1-Out)"), NetName::from("/MID"))]
+        );
+        assert!(result.moved_pins.is_empty());
+        assert_eq!(result.to_string(), "Rename net Net-(U1-Out) -> /MID\n");
+    }
+
+    #[test]
+    fn component_and_pin_changes_are_detected() {
+        let input = test_data!("kvt.net");
+        let old = NetList::parse(&input).unwrap();
+        // Move J1 pin 6 from GND to VCC, and change the value and a field of R1
+        let mut new = old.clone();
+        move_node(&mut new, "J1", "6", "VCC");
+        let r1 = new
+            .components
+            .iter_mut()
+            .find(|comp| comp.ref_des == "R1".into())
+            .unwrap();
+        r1.value = "10k".into();
+        r1.fields.push(Property {
+            name: "MPN",
+            value: "RC0603FR-0710KL",
+        });
+
+        let result = diff(&old, &new);
+        assert_eq!(
+            result.changed_components,
+            vec![(
+                RefDes::from("R1"),
+                vec![
+                    ComponentChange::Value {
+                        old: Value::from("R"),
+                        new: Value::from("10k")
+                    },
+                    ComponentChange::Field {
+                        name: "MPN",
+                        old: None,
+                        new: Some("RC0603FR-0710KL")
+                    },
+                ]
+            )]
+        );
+        assert_eq!(
+            result.moved_pins,
+            vec![PinMove {
+                ref_des: RefDes::from("J1"),
+                num: PinNum::from("6"),
+                old: NetName::from("GND"),
+                new: NetName::from("VCC"),
+            }]
+        );
+        assert!(result.renamed_nets.is_empty());
+        assert!(result.merged_nets.is_empty());
+        assert!(result.split_nets.is_empty());
+        assert!(result.connected_pins.is_empty());
+    }
+
+    #[test]
+    fn pin_connections_are_detected() {
+        let input = test_data!("kvt.net");
+        let old = NetList::parse(&input).unwrap();
+        // J1 pin 6 is left alone on a net of its own
+        let mut new = old.clone();
+        new.nets.push(Net {
+            code: "8".into(),
+            name: "unconnected-(J1-Pin_6)".into(),
+            nodes: vec![],
+        });
+        move_node(&mut new, "J1", "6", "unconnected-(J1-Pin_6)");
+
+        let disconnected = PinConnection {
+            ref_des: "J1".into(),
+            num: "6".into(),
+            net: "GND".into(),
+        };
+        let result = diff(&old, &new);
+        assert_eq!(result.disconnected_pins, vec![disconnected.clone()]);
+        assert_eq!(result.to_string(), "Disconnect pin J1.6 from GND\n");
+
+        let result = diff(&new, &old);
+        assert_eq!(result.connected_pins, vec![disconnected]);
+        assert!(result.added_nets.is_empty());
+        assert!(result.moved_pins.is_empty());
+        assert_eq!(result.to_string(), "Connect pin J1.6 to GND\n");
+    }
+
+    #[test]
+    fn merged_nets_are_detected() {
+        let input = test_data!("kvt.net");
+        let old = NetList::parse(&input).unwrap();
+        // The nodes of /B join /A
+        let mut new = old.clone();
+        let b = new.nets.remove(1);
+        assert_eq!(b.name, "/B".into());
+        new.nets[0].nodes.extend(b.nodes);
+
+        let result = diff(&new, &old);
+        assert_eq!(
+            result.split_nets,
+            vec![NetSplit {
+                old: NetName::from("/A"),
+                new: vec![NetName::from("/A"), NetName::from("/B")],
+            }]
+        );
+
+        let result = diff(&old, &new);
+        assert_eq!(
+            result.merged_nets,
+            vec![NetMerge {
+                old: vec![NetName::from("/A"), NetName::from("/B")],
+                new: NetName::from("/A"),
+            }]
+        );
+        assert!(result.moved_pins.is_empty());
+    }
+}
@@ -0,0 +1,498 @@
+//! Electrical equivalence of netlists
+//!
+//! Two netlists are equivalent if there is a one-to-one mapping of components and of nets which preserves part,
+//! value and footprint of every component, and which pin of which component every net connects to. Reference
+//! designators and net names are ignored.
+//!
+//! The mapping is found by colour refinement: components and nets are repeatedly split into classes by the classes
+//! of what they connect to, until the classes are stable. When symmetry leaves classes with several members, one
+//! pair is fixed and the search backtracks if that fails.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+
+use crate::{Footprint, NetList, NetName, PartId, RefDes, Value};
+
+/// The mapping between two equivalent netlists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equivalence<'a> {
+    /// Pairs of matching reference designators, in the order of the left netlist
+    pub components: Vec<(RefDes<'a>, RefDes<'a>)>,
+    /// Pairs of matching net names, in the order of the left netlist
+    pub nets: Vec<(NetName<'a>, NetName<'a>)>,
+}
+
+/// Why two netlists are not equivalent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch<'a> {
+    /// There are not the same number of components with this part, value and footprint
+    Components {
+        part_id: PartId<'a>,
+        value: Value<'a>,
+        footprint: Option<Footprint<'a>>,
+        left: Vec<RefDes<'a>>,
+        right: Vec<RefDes<'a>>,
+    },
+    /// There are not the same number of nets
+    NetCount { left: usize, right: usize },
+    /// These components are connected differently
+    ComponentConnections {
+        left: Vec<RefDes<'a>>,
+        right: Vec<RefDes<'a>>,
+    },
+    /// These nets are connected differently
+    NetConnections {
+        left: Vec<NetName<'a>>,
+        right: Vec<NetName<'a>>,
+    },
+    /// No consistent mapping exists even though all classes match
+    NoMapping,
+}
+
+fn join(items: impl IntoIterator<Item = impl Display>) -> String {
+    let items: Vec<String> = items.into_iter().map(|i| i.to_string()).collect();
+    if items.is_empty() {
+        "(none)".to_owned()
+    } else {
+        items.join(", ")
+    }
+}
+
+impl Display for Mismatch<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mismatch::Components {
+                part_id,
+                value,
+                left,
+                right,
+                ..
+            } => write!(
+                f,
+                "Different number of {}:{} {value}: {} vs {}",
+                part_id.lib,
+                part_id.part,
+                join(left),
+                join(right)
+            ),
+            Mismatch::NetCount { left, right } => {
+                write!(f, "Different number of nets: {left} vs {right}")
+            }
+            Mismatch::ComponentConnections { left, right } => write!(
+                f,
+                "Components connected differently: {} vs {}",
+                join(left),
+                join(right)
+            ),
+            Mismatch::NetConnections { left, right } => write!(
+                f,
+                "Nets connected differently: {} vs {}",
+                join(left),
+                join(right)
+            ),
+            Mismatch::NoMapping => write!(f, "No consistent mapping found"),
+        }
+    }
+}
+
+/// A netlist as a bipartite graph of components and nets, with edges labelled by pin number
+struct Graph<'n, 'a> {
+    netlist: &'n NetList<'a>,
+    comp_edges: Vec<Vec<(&'a str, usize)>>,
+    net_edges: Vec<Vec<(usize, &'a str)>>,
+}
+
+impl<'n, 'a> Graph<'n, 'a> {
+    fn new(netlist: &'n NetList<'a>) -> Self {
+        let index: HashMap<RefDes<'a>, usize> = netlist
+            .components
+            .iter()
+            .enumerate()
+            .map(|(i, comp)| (comp.ref_des, i))
+            .collect();
+
+        let mut comp_edges = vec![vec![]; netlist.components.len()];
+        let mut net_edges = vec![vec![]; netlist.nets.len()];
+        for (n, net) in netlist.nets.iter().enumerate() {
+            for node in net.nodes.iter() {
+                if let Some(&c) = index.get(&node.ref_des) {
+                    comp_edges[c].push((node.num.as_str(), n));
+                    net_edges[n].push((c, node.num.as_str()));
+                }
+            }
+        }
+
+        Self {
+            netlist,
+            comp_edges,
+            net_edges,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Colors {
+    comps: Vec<usize>,
+    nets: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Signature<'a> {
+    Comp(usize, Vec<(&'a str, usize)>),
+    Net(usize, Vec<(usize, &'a str)>),
+}
+
+/// A colour class with different sizes on the two sides
+enum ClassMismatch {
+    Comp(usize),
+    Net(usize),
+}
+
+fn recolor<'a>(
+    graph: &Graph<'_, 'a>,
+    colors: &Colors,
+    ids: &mut HashMap<Signature<'a>, usize>,
+) -> Colors {
+    let mut id = |sig| {
+        let n = ids.len();
+        *ids.entry(sig).or_insert(n)
+    };
+
+    let comps = graph
+        .comp_edges
+        .iter()
+        .enumerate()
+        .map(|(c, edges)| {
+            let mut sig: Vec<_> = edges
+                .iter()
+                .map(|&(pin, n)| (pin, colors.nets[n]))
+                .collect();
+            sig.sort();
+            id(Signature::Comp(colors.comps[c], sig))
+        })
+        .collect();
+    let nets = graph
+        .net_edges
+        .iter()
+        .enumerate()
+        .map(|(n, edges)| {
+            let mut sig: Vec<_> = edges
+                .iter()
+                .map(|&(c, pin)| (colors.comps[c], pin))
+                .collect();
+            sig.sort();
+            id(Signature::Net(colors.nets[n], sig))
+        })
+        .collect();
+
+    Colors { comps, nets }
+}
+
+fn histogram(colors: &[usize]) -> HashMap<usize, usize> {
+    let mut counts = HashMap::new();
+    for &c in colors {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Find the smallest class with different sizes on the two sides
+fn class_mismatch(left: &Colors, right: &Colors) -> Option<ClassMismatch> {
+    let mut worst: Option<(usize, ClassMismatch)> = None;
+    let mut check = |l: &[usize], r: &[usize], make: fn(usize) -> ClassMismatch| {
+        let (lh, rh) = (histogram(l), histogram(r));
+        let colors: BTreeSet<usize> = lh.keys().chain(rh.keys()).copied().collect();
+        for color in colors {
+            let (a, b) = (
+                lh.get(&color).copied().unwrap_or(0),
+                rh.get(&color).copied().unwrap_or(0),
+            );
+            if a != b && !matches!(&worst, Some((size, _)) if *size <= a + b) {
+                worst = Some((a + b, make(color)));
+            }
+        }
+    };
+    check(&left.comps, &right.comps, ClassMismatch::Comp);
+    check(&left.nets, &right.nets, ClassMismatch::Net);
+    worst.map(|(_, mismatch)| mismatch)
+}
+
+/// Refine the colours of both graphs until they are stable
+///
+/// If a class ends up with different sizes on the two sides, the colours at that point are returned with the error.
+fn refine<'a>(
+    left: &Graph<'_, 'a>,
+    right: &Graph<'_, 'a>,
+    mut lc: Colors,
+    mut rc: Colors,
+) -> Result<(Colors, Colors), (ClassMismatch, Colors, Colors)> {
+    let mut classes = 0;
+    loop {
+        let mut ids = HashMap::new();
+        lc = recolor(left, &lc, &mut ids);
+        rc = recolor(right, &rc, &mut ids);
+
+        if let Some(mismatch) = class_mismatch(&lc, &rc) {
+            return Err((mismatch, lc, rc));
+        }
+        if ids.len() == classes {
+            return Ok((lc, rc));
+        }
+        classes = ids.len();
+    }
+}
+
+/// List the members of a mismatched class
+fn explain<'a>(
+    left: &Graph<'_, 'a>,
+    right: &Graph<'_, 'a>,
+    mismatch: ClassMismatch,
+    lc: &Colors,
+    rc: &Colors,
+) -> Mismatch<'a> {
+    fn members(colors: &[usize], color: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..colors.len()).filter(move |&i| colors[i] == color)
+    }
+
+    match mismatch {
+        ClassMismatch::Comp(color) => {
+            let refs = |g: &Graph<'_, 'a>, colors: &Colors| {
+                members(&colors.comps, color)
+                    .map(|c| g.netlist.components[c].ref_des)
+                    .collect()
+            };
+            Mismatch::ComponentConnections {
+                left: refs(left, lc),
+                right: refs(right, rc),
+            }
+        }
+        ClassMismatch::Net(color) => {
+            let names = |g: &Graph<'_, 'a>, colors: &Colors| {
+                members(&colors.nets, color)
+                    .map(|n| g.netlist.nets[n].name)
+                    .collect()
+            };
+            Mismatch::NetConnections {
+                left: names(left, lc),
+                right: names(right, rc),
+            }
+        }
+    }
+}
+
+/// Fix pairs of elements in ambiguous classes until every class has a single member on each side
+fn search<'a>(
+    left: &Graph<'_, 'a>,
+    right: &Graph<'_, 'a>,
+    lc: Colors,
+    rc: Colors,
+) -> Option<(Colors, Colors)> {
+    let ambiguous = |colors: &[usize]| {
+        let counts = histogram(colors);
+        (0..colors.len())
+            .filter(|&i| counts[&colors[i]] > 1)
+            .min_by_key(|&i| counts[&colors[i]])
+    };
+
+    let fresh = lc
+        .comps
+        .iter()
+        .chain(lc.nets.iter())
+        .chain(rc.comps.iter())
+        .chain(rc.nets.iter())
+        .max()
+        .map_or(0, |m| m + 1);
+
+    let (is_comp, x) = match (ambiguous(&lc.comps), ambiguous(&lc.nets)) {
+        (Some(x), _) => (true, x),
+        (None, Some(x)) => (false, x),
+        (None, None) => return Some((lc, rc)),
+    };
+
+    let (l_colors, r_colors) = if is_comp {
+        (&lc.comps, &rc.comps)
+    } else {
+        (&lc.nets, &rc.nets)
+    };
+    let candidates: Vec<usize> = (0..r_colors.len())
+        .filter(|&y| r_colors[y] == l_colors[x])
+        .collect();
+
+    for y in candidates {
+        let (mut lc, mut rc) = (lc.clone(), rc.clone());
+        if is_comp {
+            lc.comps[x] = fresh;
+            rc.comps[y] = fresh;
+        } else {
+            lc.nets[x] = fresh;
+            rc.nets[y] = fresh;
+        }
+        if let Ok((lc, rc)) = refine(left, right, lc, rc) {
+            if let Some(result) = search(left, right, lc, rc) {
+                return Some(result);
+            }
+        }
+    }
+
+    None
+}
+
+/// Decide whether two netlists are electrically equivalent
+///
+/// If they are, the mapping of components and nets is returned. Otherwise a mismatch explaining the difference is
+/// returned.
+pub fn equivalent<'a>(
+    left: &NetList<'a>,
+    right: &NetList<'a>,
+) -> Result<Equivalence<'a>, Mismatch<'a>> {
+    let label = |netlist: &NetList<'a>, c: usize| {
+        let comp = &netlist.components[c];
+        (comp.part_id, comp.value, comp.footprint)
+    };
+
+    // Start with components coloured by their label, and all nets the same colour
+    let mut labels: HashMap<(PartId<'a>, Value<'a>, Option<Footprint<'a>>), usize> = HashMap::new();
+    let mut initial = |netlist: &NetList<'a>| {
+        let comps = (0..netlist.components.len())
+            .map(|c| {
+                let n = labels.len();
+                *labels.entry(label(netlist, c)).or_insert(n)
+            })
+            .collect();
+        Colors {
+            comps,
+            nets: vec![usize::MAX; netlist.nets.len()],
+        }
+    };
+    let lc = initial(left);
+    let rc = initial(right);
+
+    if let Some(ClassMismatch::Comp(color)) = class_mismatch(
+        &Colors {
+            comps: lc.comps.clone(),
+            nets: vec![],
+        },
+        &Colors {
+            comps: rc.comps.clone(),
+            nets: vec![],
+        },
+    ) {
+        let members = |netlist: &NetList<'a>, colors: &Colors| -> Vec<RefDes<'a>> {
+            (0..colors.comps.len())
+                .filter(|&c| colors.comps[c] == color)
+                .map(|c| netlist.components[c].ref_des)
+                .collect()
+        };
+        let (l, r) = (members(left, &lc), members(right, &rc));
+        let comp = l
+            .first()
+            .and_then(|r| left.find_component(*r))
+            .or_else(|| r.first().and_then(|r| right.find_component(*r)))
+            .unwrap();
+        return Err(Mismatch::Components {
+            part_id: comp.part_id,
+            value: comp.value,
+            footprint: comp.footprint,
+            left: l,
+            right: r,
+        });
+    }
+
+    if left.nets.len() != right.nets.len() {
+        return Err(Mismatch::NetCount {
+            left: left.nets.len(),
+            right: right.nets.len(),
+        });
+    }
+
+    let lg = Graph::new(left);
+    let rg = Graph::new(right);
+
+    let (lc, rc) = refine(&lg, &rg, lc, rc)
+        .map_err(|(mismatch, lc, rc)| explain(&lg, &rg, mismatch, &lc, &rc))?;
+
+    let (lc, rc) = search(&lg, &rg, lc, rc).ok_or(Mismatch::NoMapping)?;
+
+    let pair = |l: &[usize], r: &[usize]| -> Vec<(usize, usize)> {
+        let index: HashMap<usize, usize> = r.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+        l.iter().enumerate().map(|(i, c)| (i, index[c])).collect()
+    };
+
+    Ok(Equivalence {
+        components: pair(&lc.comps, &rc.comps)
+            .into_iter()
+            .map(|(l, r)| (left.components[l].ref_des, right.components[r].ref_des))
+            .collect(),
+        nets: pair(&lc.nets, &rc.nets)
+            .into_iter()
+            .map(|(l, r)| (left.nets[l].name, right.nets[r].name))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{component_mut, move_node, rename_component, rename_net};
+
+    #[test]
+    fn renamed_netlist_is_equivalent() {
+        let input = test_data!("kvt.net");
+        let left = NetList::parse(&input).unwrap();
+        // Swap the names of U1 and U2, and rename a net
+        let mut right = left.clone();
+        rename_component(&mut right, "U1", "TMP");
+        rename_component(&mut right, "U2", "U1");
+        rename_component(&mut right, "TMP", "U2");
+        rename_net(&mut right, "/OUT", "/Q");
+
+        let result = equivalent(&left, &right).unwrap();
+        assert!(result
+            .components
+            .contains(&(RefDes::from("U1"), RefDes::from("U2"))));
+        assert!(result
+            .components
+            .contains(&(RefDes::from("U2"), RefDes::from("U1"))));
+        assert!(result
+            .nets
+            .contains(&(NetName::from("/OUT"), NetName::from("/Q"))));
+    }
+
+    #[test]
+    fn changed_connection_is_not_equivalent() {
+        let input = test_data!("kvt.net");
+        let left = NetList::parse(&input).unwrap();
+        // Connect R1 to U1 instead of U2
+        let mut right = left.clone();
+        move_node(&mut right, "U1", "2", "Net-(U2-B)");
+        move_node(&mut right, "U2", "2", "/B");
+
+        assert!(matches!(
+            equivalent(&left, &right),
+            Err(Mismatch::ComponentConnections { .. })
+        ));
+    }
+
+    #[test]
+    fn changed_value_is_not_equivalent() {
+        let input = test_data!("kvt.net");
+        let left = NetList::parse(&input).unwrap();
+        let mut right = left.clone();
+        component_mut(&mut right, "R1").value = "10k".into();
+
+        let result = equivalent(&left, &right);
+        assert_eq!(
+            result,
+            Err(Mismatch::Components {
+                part_id: PartId {
+                    lib: "Device",
+                    part: "R"
+                },
+                value: Value::from("R"),
+                footprint: None,
+                left: vec![RefDes::from("R1")],
+                right: vec![],
+            })
+        );
+    }
+}
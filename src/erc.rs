@@ -0,0 +1,461 @@
+//! Electrical rules check
+//!
+//! The check works on the pin types of the nodes of each net, in the same way as the pin conflict matrix of Eeschema.
+//! Every pair of pin types on a net is looked up in a [`PinConflictMatrix`], and a few rules look at the net as a
+//! whole. The severity of each rule can be adjusted with an [`ErcConfig`].
+
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use crate::{Net, NetList, NetName, PinNum, PinType, RefDes};
+
+const PIN_TYPES: usize = 11;
+
+/// How serious a violation is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Ignore,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Ignore => write!(f, "ignore"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The severity of connecting two pin types to the same net
+///
+/// The matrix is symmetric. The default follows the defaults of Eeschema. Pins marked `no_connect` are not part of
+/// the matrix, since connecting them to anything at all is checked on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinConflictMatrix {
+    matrix: [[Severity; PIN_TYPES]; PIN_TYPES],
+}
+
+impl PinConflictMatrix {
+    /// A matrix which allows every connection
+    pub fn permissive() -> Self {
+        Self {
+            matrix: [[Severity::Ignore; PIN_TYPES]; PIN_TYPES],
+        }
+    }
+
+    /// The severity of connecting two pin types, which is [`Severity::Ignore`] for `no_connect` pins
+    pub fn get(&self, a: PinType, b: PinType) -> Severity {
+        match (index(a), index(b)) {
+            (Some(a), Some(b)) => self.matrix[a][b],
+            _ => Severity::Ignore,
+        }
+    }
+
+    /// Set the severity of connecting two pin types, which has no effect for `no_connect` pins
+    pub fn set(&mut self, a: PinType, b: PinType, severity: Severity) {
+        if let (Some(a), Some(b)) = (index(a), index(b)) {
+            self.matrix[a][b] = severity;
+            self.matrix[b][a] = severity;
+        }
+    }
+}
+
+impl Default for PinConflictMatrix {
+    fn default() -> Self {
+        use Severity::{Error as E, Ignore as O, Warning as W};
+
+        Self {
+            matrix: [
+                // I  O  Bi 3S Pa Fr Un PI PO OC OE
+                [O, O, O, O, O, O, W, O, O, O, O], // Input
+                [O, E, O, W, O, O, W, O, E, E, E], // Output
+                [O, O, O, O, O, O, W, O, W, O, W], // Bidirectional
+                [O, W, O, O, O, O, W, W, E, W, W], // TriState
+                [O, O, O, O, O, O, W, O, O, O, O], // Passive
+                [O, O, O, O, O, O, O, O, O, O, O], // Free
+                [W, W, W, W, W, O, W, W, W, W, W], // Unspecified
+                [O, O, O, W, O, O, W, O, O, O, O], // PowerInput
+                [O, E, W, E, O, O, W, O, E, E, E], // PowerOutput
+                [O, E, O, W, O, O, W, O, E, O, O], // OpenCollector
+                [O, E, W, W, O, O, W, O, E, O, O], // OpenEmitter
+            ],
+        }
+    }
+}
+
+/// The row of a pin type in the matrix, which is `None` for `no_connect` pins
+fn index(typ: PinType) -> Option<usize> {
+    Some(match typ {
+        PinType::Input => 0,
+        PinType::Output => 1,
+        PinType::Bidirectional => 2,
+        PinType::TriState => 3,
+        PinType::Passive => 4,
+        PinType::Free => 5,
+        PinType::Unspecified => 6,
+        PinType::PowerInput => 7,
+        PinType::PowerOutput => 8,
+        PinType::OpenCollector => 9,
+        PinType::OpenEmitter => 10,
+        PinType::Unconnected => return None,
+    })
+}
+
+/// Settings for the electrical rules check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErcConfig {
+    pub pin_conflicts: PinConflictMatrix,
+    /// An input pin on a net without anything driving it
+    pub undriven_input: Severity,
+    /// A power input pin on a net without a power output pin
+    pub unpowered_power_input: Severity,
+    /// A pin which is not connected to anything and not marked as `no_connect`
+    pub unconnected_pin: Severity,
+    /// A pin marked as `no_connect` on a net with other pins
+    pub connected_no_connect: Severity,
+}
+
+impl Default for ErcConfig {
+    fn default() -> Self {
+        Self {
+            pin_conflicts: PinConflictMatrix::default(),
+            undriven_input: Severity::Error,
+            unpowered_power_input: Severity::Error,
+            unconnected_pin: Severity::Warning,
+            connected_no_connect: Severity::Error,
+        }
+    }
+}
+
+/// The rule broken by a violation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    PinConflict(PinType, PinType),
+    UndrivenInput,
+    UnpoweredPowerInput,
+    UnconnectedPin,
+    ConnectedNoConnect,
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rule::PinConflict(a, b) => write!(f, "{a} pin connected to {b} pin"),
+            Rule::UndrivenInput => write!(f, "input pin not driven"),
+            Rule::UnpoweredPowerInput => write!(f, "power input pin not driven by a power output"),
+            Rule::UnconnectedPin => write!(f, "pin not connected"),
+            Rule::ConnectedNoConnect => write!(f, "no_connect pin connected"),
+        }
+    }
+}
+
+/// A single broken rule on a net
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation<'a> {
+    pub severity: Severity,
+    pub rule: Rule,
+    pub net: NetName<'a>,
+    /// The pins involved in the violation
+    pub pins: Vec<(RefDes<'a>, PinNum<'a>)>,
+}
+
+impl Display for Violation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pins: Vec<String> = self
+            .pins
+            .iter()
+            .map(|(ref_des, num)| format!("{ref_des}.{num}"))
+            .collect();
+        write!(
+            f,
+            "{}: {}: {} ({})",
+            self.severity,
+            self.net,
+            self.rule,
+            pins.join(", ")
+        )
+    }
+}
+
+/// The result of an electrical rules check
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErcReport<'a> {
+    pub violations: Vec<Violation<'a>>,
+}
+
+impl<'a> ErcReport<'a> {
+    pub fn errors(&self) -> impl Iterator<Item = &Violation<'a>> {
+        self.with_severity(Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Violation<'a>> {
+        self.with_severity(Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Violation<'a>> {
+        self.violations
+            .iter()
+            .filter(move |violation| violation.severity == severity)
+    }
+}
+
+impl Display for ErcReport<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for violation in self.violations.iter() {
+            writeln!(f, "{violation}")?;
+        }
+        let errors = self.errors().count();
+        let warnings = self.warnings().count();
+        writeln!(f, "{errors} errors, {warnings} warnings")
+    }
+}
+
+/// Run the electrical rules check on every net of the netlist
+pub fn check<'a>(netlist: &NetList<'a>, config: &ErcConfig) -> ErcReport<'a> {
+    let mut violations = vec![];
+    for net in netlist.nets.iter() {
+        check_net(net, config, &mut violations);
+    }
+    violations.retain(|violation| violation.severity != Severity::Ignore);
+    ErcReport { violations }
+}
+
+fn check_net<'a>(net: &Net<'a>, config: &ErcConfig, violations: &mut Vec<Violation<'a>>) {
+    let violation = |severity, rule, pins| Violation {
+        severity,
+        rule,
+        net: net.name,
+        pins,
+    };
+
+    if let [node] = net.nodes.as_slice() {
+        if node.typ != PinType::Unconnected {
+            violations.push(violation(
+                config.unconnected_pin,
+                Rule::UnconnectedPin,
+                vec![(node.ref_des, node.num)],
+            ));
+        }
+        return;
+    }
+
+    let pins_of = |typ: PinType| -> Vec<(RefDes<'a>, PinNum<'a>)> {
+        net.nodes
+            .iter()
+            .filter(|node| node.typ == typ)
+            .map(|node| (node.ref_des, node.num))
+            .collect()
+    };
+    let has = |types: &[PinType]| net.nodes.iter().any(|node| types.contains(&node.typ));
+
+    let no_connects = pins_of(PinType::Unconnected);
+    if !no_connects.is_empty() {
+        violations.push(violation(
+            config.connected_no_connect,
+            Rule::ConnectedNoConnect,
+            no_connects,
+        ));
+    }
+
+    // Only the first pair of pins is reported for each pair of pin types
+    let mut seen = HashSet::new();
+    for (i, a) in net.nodes.iter().enumerate() {
+        for b in net.nodes[i + 1..].iter() {
+            let severity = config.pin_conflicts.get(a.typ, b.typ);
+            let key = (
+                index(a.typ).min(index(b.typ)),
+                index(a.typ).max(index(b.typ)),
+            );
+            if severity != Severity::Ignore && seen.insert(key) {
+                violations.push(violation(
+                    severity,
+                    Rule::PinConflict(a.typ, b.typ),
+                    vec![(a.ref_des, a.num), (b.ref_des, b.num)],
+                ));
+            }
+        }
+    }
+
+    let inputs = pins_of(PinType::Input);
+    if !inputs.is_empty()
+        && !has(&[
+            PinType::Output,
+            PinType::Bidirectional,
+            PinType::TriState,
+            PinType::Passive,
+            PinType::Unspecified,
+            PinType::PowerOutput,
+            PinType::OpenCollector,
+            PinType::OpenEmitter,
+        ])
+    {
+        violations.push(violation(
+            config.undriven_input,
+            Rule::UndrivenInput,
+            inputs,
+        ));
+    }
+
+    let power_inputs = pins_of(PinType::PowerInput);
+    if !power_inputs.is_empty() && !has(&[PinType::PowerOutput]) {
+        violations.push(violation(
+            config.unpowered_power_input,
+            Rule::UnpoweredPowerInput,
+            power_inputs,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::node_mut;
+
+    fn rules(report: &ErcReport<'_>) -> Vec<(Severity, Rule, String)> {
+        report
+            .violations
+            .iter()
+            .map(|v| (v.severity, v.rule, v.net.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn power_nets_without_power_output_are_reported() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let report = check(&netlist, &ErcConfig::default());
+
+        assert_eq!(
+            rules(&report),
+            vec![
+                (Severity::Error, Rule::UnpoweredPowerInput, "GND".to_owned()),
+                (Severity::Error, Rule::UnpoweredPowerInput, "VCC".to_owned()),
+            ]
+        );
+        assert_eq!(
+            report.violations[0].pins,
+            vec![("U1".into(), "3".into()), ("U2".into(), "3".into())]
+        );
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn severities_can_be_adjusted() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let config = ErcConfig {
+            unpowered_power_input: Severity::Ignore,
+            ..Default::default()
+        };
+        let report = check(&netlist, &config);
+
+        assert!(report.violations.is_empty());
+        assert_eq!(report.to_string(), "0 errors, 0 warnings\n");
+    }
+
+    #[test]
+    fn output_to_output_is_a_conflict() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        node_mut(&mut netlist, "U2", "1").typ = PinType::Output;
+        let config = ErcConfig {
+            unpowered_power_input: Severity::Ignore,
+            ..Default::default()
+        };
+        let report = check(&netlist, &config);
+
+        assert_eq!(
+            rules(&report),
+            vec![(
+                Severity::Error,
+                Rule::PinConflict(PinType::Output, PinType::Output),
+                "Net-(U1-Out)".to_owned()
+            )]
+        );
+        assert_eq!(
+            report.violations[0].to_string(),
+            "error: Net-(U1-Out): output pin connected to output pin (U1.4, U2.1)"
+        );
+
+        let mut config = config;
+        config
+            .pin_conflicts
+            .set(PinType::Output, PinType::Output, Severity::Warning);
+        let report = check(&netlist, &config);
+        assert!(!report.has_errors());
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[test]
+    fn unconnected_and_undriven_pins_are_reported() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        node_mut(&mut netlist, "J1", "3").typ = PinType::Input;
+        netlist.nets[0]
+            .nodes
+            .retain(|node| node.ref_des != RefDes::from("J1"));
+        let config = ErcConfig {
+            unpowered_power_input: Severity::Ignore,
+            ..Default::default()
+        };
+        let report = check(&netlist, &config);
+
+        assert_eq!(
+            rules(&report),
+            vec![
+                (Severity::Warning, Rule::UnconnectedPin, "/A".to_owned()),
+                (Severity::Error, Rule::UndrivenInput, "/B".to_owned()),
+            ]
+        );
+        assert_eq!(
+            report.violations[1].pins,
+            vec![("J1".into(), "3".into()), ("U1".into(), "2".into())]
+        );
+    }
+
+    #[test]
+    fn unspecified_pins_are_warnings() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        node_mut(&mut netlist, "J1", "2").typ = PinType::Unspecified;
+        let config = ErcConfig {
+            unpowered_power_input: Severity::Ignore,
+            ..Default::default()
+        };
+        let report = check(&netlist, &config);
+
+        assert_eq!(
+            rules(&report),
+            vec![(
+                Severity::Warning,
+                Rule::PinConflict(PinType::Unspecified, PinType::Input),
+                "/A".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn no_connect_pins_must_not_be_connected() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        node_mut(&mut netlist, "U1", "1").typ = PinType::Unconnected;
+        let config = ErcConfig {
+            unpowered_power_input: Severity::Ignore,
+            ..Default::default()
+        };
+        let report = check(&netlist, &config);
+
+        assert_eq!(
+            rules(&report),
+            vec![(Severity::Error, Rule::ConnectedNoConnect, "/A".to_owned())]
+        );
+        assert_eq!(report.violations[0].pins, vec![("U1".into(), "1".into())]);
+    }
+}
@@ -29,3 +29,81 @@ pub enum ParseError {
     #[error("Unexpected root label {0}")]
     UnexpectedRootLabel(String),
 }
+
+#[derive(Error, Debug)]
+pub enum VerilogError {
+    #[error("Component {0} has no pin {1}")]
+    UnknownPin(String, String),
+    #[error("Unterminated pin list in VerilogCode of component {0}")]
+    UnterminatedTemplate(String),
+    #[error("Net {0} not found")]
+    UnknownNet(String),
+}
+
+#[derive(Error, Debug)]
+pub enum SimError {
+    #[error("Component {0} has no pin {1}")]
+    UnknownPin(String, String),
+    #[error("Net {0} not found")]
+    UnknownNet(String),
+    #[error("Simulation did not settle after {0} evaluations")]
+    Oscillation(usize),
+}
+
+#[derive(Error, Debug)]
+pub enum LibraryError {
+    #[error("Could not read {path}: {source}")]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Could not parse {path}: {source}")]
+    Parse {
+        path: std::path::PathBuf,
+        source: ParseError,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum SchematicError {
+    #[error("Could not read {path}: {source}")]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Could not parse {path}: {source}")]
+    Parse {
+        path: std::path::PathBuf,
+        source: ParseError,
+    },
+    #[error("Invalid number {0}")]
+    InvalidNumber(String),
+    #[error("Symbol {0} not found in the schematic")]
+    MissingLibSymbol(String),
+    #[error("Sheet {0} contains itself")]
+    RecursiveSheet(String),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    #[error("Part {0} added twice")]
+    DuplicatePart(String),
+    #[error("Component {0} added twice")]
+    DuplicateComponent(String),
+    #[error("Net {0} added twice")]
+    DuplicateNet(String),
+    #[error("Part {0} not found")]
+    MissingPart(String),
+    #[error("Component {0} not found")]
+    MissingComponent(String),
+    #[error("Component {0} has no pin {1}")]
+    UnknownPin(String, String),
+    #[error("Net {0} not found")]
+    UnknownNet(String),
+    #[error("Pin {1} of component {0} is connected to both {2} and {3}")]
+    ConflictingNets(String, String, String, String),
+    #[error("No net found for component {0}, pin {1}")]
+    MissingNet(String, String),
+    #[error("Unused part {0}")]
+    UnusedPart(String),
+}
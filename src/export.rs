@@ -0,0 +1,206 @@
+//! Export the netlist in the formats understood by other layout tools
+
+mod allegro;
+mod cadstar;
+mod edif;
+mod orcad;
+mod pads;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{Component, NetList, RefDes};
+
+pub use allegro::to_allegro;
+pub use cadstar::to_cadstar;
+pub use edif::to_edif;
+pub use orcad::to_orcad_pcb2;
+pub use pads::to_pads;
+
+/// The footprint written for components which have none
+const NO_FOOTPRINT: &str = "$noname";
+
+/// The footprint of a component, or [`NO_FOOTPRINT`] if it has none
+fn footprint<'a>(comp: &Component<'a>) -> &'a str {
+    match comp.footprint {
+        Some(footprint) if !footprint.as_str().is_empty() => footprint.as_str(),
+        _ => NO_FOOTPRINT,
+    }
+}
+
+/// The netlist without the components marked `exclude_from_board`, which have no place in a layout
+pub(crate) fn board_netlist<'a>(netlist: &NetList<'a>) -> NetList<'a> {
+    let excluded: Vec<RefDes<'a>> = netlist
+        .components
+        .iter()
+        .filter(|comp| comp.find_property("exclude_from_board").is_some())
+        .map(|comp| comp.ref_des)
+        .collect();
+    let mut netlist = netlist.clone();
+    netlist.remove_components(&excluded);
+    netlist
+}
+
+/// Names which had to be changed to be legal in an exported netlist
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NameMap {
+    /// Pairs of original and exported reference designators
+    pub ref_des: Vec<(String, String)>,
+    /// Pairs of original and exported net names
+    pub nets: Vec<(String, String)>,
+    /// Pairs of original and exported footprints
+    pub footprints: Vec<(String, String)>,
+    /// Pairs of original and exported component values
+    pub values: Vec<(String, String)>,
+    /// Pairs of original and exported pin numbers
+    pub pins: Vec<(String, String)>,
+}
+
+impl NameMap {
+    pub fn is_empty(&self) -> bool {
+        self.ref_des.is_empty()
+            && self.nets.is_empty()
+            && self.footprints.is_empty()
+            && self.values.is_empty()
+            && self.pins.is_empty()
+    }
+}
+
+impl std::fmt::Display for NameMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (title, names) in [
+            ("references", &self.ref_des),
+            ("nets", &self.nets),
+            ("footprints", &self.footprints),
+            ("values", &self.values),
+            ("pins", &self.pins),
+        ] {
+            if names.is_empty() {
+                continue;
+            }
+            writeln!(f, "Renamed {title}:")?;
+            for (from, to) in names.iter() {
+                writeln!(f, "  {from} -> {to}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Make names legal for an output format
+///
+/// Illegal characters are replaced by `_` and long names are truncated. If this makes two names collide a numeric
+/// suffix is added. Names which are already legal are never changed, so the result only depends on the set of names
+/// and the order they are given in.
+struct Mangler {
+    names: HashMap<String, String>,
+    renamed: Vec<(String, String)>,
+}
+
+impl Mangler {
+    fn new<'n>(
+        names: impl IntoIterator<Item = &'n str>,
+        legal: fn(char) -> bool,
+        max_len: usize,
+    ) -> Self {
+        let names: Vec<&str> = names.into_iter().collect();
+        let is_legal = |name: &str| {
+            !name.is_empty() && name.chars().count() <= max_len && name.chars().all(legal)
+        };
+
+        let mut used: HashSet<String> = names
+            .iter()
+            .filter(|name| is_legal(name))
+            .map(|name| name.to_string())
+            .collect();
+        let mut mapped: HashMap<String, String> = used
+            .iter()
+            .map(|name| (name.clone(), name.clone()))
+            .collect();
+        let mut renamed = vec![];
+
+        for name in names {
+            if mapped.contains_key(name) {
+                continue;
+            }
+
+            let base: String = name
+                .chars()
+                .map(|c| if legal(c) { c } else { '_' })
+                .take(max_len)
+                .collect();
+            let base = if base.is_empty() {
+                "_".to_owned()
+            } else {
+                base
+            };
+
+            let mut new_name = base.clone();
+            let mut n = 1;
+            while used.contains(&new_name) {
+                n += 1;
+                let suffix = format!("_{n}");
+                let keep = max_len.saturating_sub(suffix.len());
+                new_name = base.chars().take(keep).collect::<String>() + &suffix;
+            }
+
+            used.insert(new_name.clone());
+            mapped.insert(name.to_owned(), new_name.clone());
+            renamed.push((name.to_owned(), new_name));
+        }
+
+        Self {
+            names: mapped,
+            renamed,
+        }
+    }
+
+    fn get<'s>(&'s self, name: &'s str) -> &'s str {
+        self.names.get(name).map(|s| s.as_str()).unwrap_or(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Property;
+
+    #[test]
+    fn excluded_components_are_not_exported() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        let r1 = netlist
+            .components
+            .iter_mut()
+            .find(|comp| comp.ref_des.as_str() == "R1")
+            .unwrap();
+        r1.properties.push(Property {
+            name: "exclude_from_board",
+            value: "",
+        });
+
+        assert!(!to_orcad_pcb2(&netlist).contains(" R1 "));
+        assert!(!to_cadstar(&netlist).0.contains("R1"));
+        assert!(!to_allegro(&netlist).0.contains("R1"));
+        assert!(!to_pads(&netlist).contains("R1"));
+        assert!(!to_edif(&netlist, "kvt").contains("R1"));
+    }
+
+    #[test]
+    fn mangler_is_deterministic() {
+        let legal = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let mangler = Mangler::new(["A B", "A_B", "A-B", "LONG_NAME"], legal, 6);
+
+        assert_eq!(mangler.get("A_B"), "A_B");
+        assert_eq!(mangler.get("A B"), "A_B_2");
+        assert_eq!(mangler.get("A-B"), "A_B_3");
+        assert_eq!(mangler.get("LONG_NAME"), "LONG_N");
+        assert_eq!(
+            mangler.renamed,
+            vec![
+                ("A B".to_owned(), "A_B_2".to_owned()),
+                ("A-B".to_owned(), "A_B_3".to_owned()),
+                ("LONG_NAME".to_owned(), "LONG_N".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt::Write;
+
+use super::{board_netlist, footprint, Mangler, NameMap};
+use crate::{Footprint, NetList};
+
+/// Allegro limits reference designators and net names to 31 characters
+const MAX_NAME_LEN: usize = 31;
+
+/// Longest line written before a list is continued on the next line
+const MAX_LINE_LEN: usize = 80;
+
+fn legal_ref_des(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn legal_net_name(c: char) -> bool {
+    c.is_ascii_graphic() && !matches!(c, '!' | '\'' | '"' | ';' | ',')
+}
+
+/// Packages and values are quoted, so anything but a quote is allowed
+fn legal_quoted(c: char) -> bool {
+    c != '\'' && !c.is_control()
+}
+
+/// Pin numbers follow the node's reference designator after a `.`
+fn legal_pin_num(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn quoted(s: &str) -> String {
+    format!("'{s}'")
+}
+
+/// Append items to a line, continuing long lines with a trailing `,`
+fn write_list<'s>(out: &mut String, head: String, items: impl IntoIterator<Item = &'s str>) {
+    let mut line = head;
+    for item in items {
+        if line.len() + item.len() + 1 > MAX_LINE_LEN && !line.ends_with(';') {
+            writeln!(out, "{line},").unwrap();
+            line = " ".to_owned();
+        }
+        line.push(' ');
+        line.push_str(item);
+    }
+    writeln!(out, "{line}").unwrap();
+}
+
+/// Generate an Allegro netlist in the Telesis format
+///
+/// Reference designators, net names, packages, values and pin numbers which are illegal in Allegro are renamed, and
+/// the renamed names are returned together with the netlist. Components without a footprint get the package
+/// `$noname`.
+pub fn to_allegro(netlist: &NetList<'_>) -> (String, NameMap) {
+    let netlist = &board_netlist(netlist);
+    let refs = Mangler::new(
+        netlist.components.iter().map(|comp| comp.ref_des.as_str()),
+        legal_ref_des,
+        MAX_NAME_LEN,
+    );
+    let nets = Mangler::new(
+        netlist.nets.iter().map(|net| net.name.as_str()),
+        legal_net_name,
+        MAX_NAME_LEN,
+    );
+    let package_names = Mangler::new(
+        netlist
+            .components
+            .iter()
+            .map(|comp| Footprint::from(footprint(comp)).name()),
+        legal_quoted,
+        usize::MAX,
+    );
+    let values = Mangler::new(
+        netlist.components.iter().map(|comp| comp.value.as_str()),
+        legal_quoted,
+        usize::MAX,
+    );
+    let pins = Mangler::new(
+        netlist
+            .nets
+            .iter()
+            .flat_map(|net| net.nodes.iter())
+            .map(|node| node.num.as_str()),
+        legal_pin_num,
+        MAX_NAME_LEN,
+    );
+
+    let mut packages: Vec<((&str, &str), Vec<&str>)> = vec![];
+    for comp in netlist.components.iter() {
+        let package = package_names.get(Footprint::from(footprint(comp)).name());
+        let key = (package, values.get(comp.value.as_str()));
+        let ref_des = refs.get(comp.ref_des.as_str());
+        match packages.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, refs)) => refs.push(ref_des),
+            None => packages.push((key, vec![ref_des])),
+        }
+    }
+
+    let mut out = String::new();
+
+    writeln!(out, "(NETLIST)").unwrap();
+    if let Some(source) = netlist.design.source {
+        writeln!(out, "(Source: {source})").unwrap();
+    }
+    if let Some(date) = netlist.design.date {
+        writeln!(out, "(Date: {date})").unwrap();
+    }
+    writeln!(out).unwrap();
+
+    writeln!(out, "$PACKAGES").unwrap();
+    for ((package, value), refs) in packages {
+        let head = format!("{} ! {} ;", quoted(package), quoted(value));
+        write_list(&mut out, head, refs);
+    }
+
+    writeln!(out, "$NETS").unwrap();
+    for net in netlist.nets.iter() {
+        let nodes: Vec<String> = net
+            .nodes
+            .iter()
+            .map(|node| {
+                let ref_des = refs.get(node.ref_des.as_str());
+                format!("{ref_des}.{}", pins.get(node.num.as_str()))
+            })
+            .collect();
+        let head = format!("{} ;", quoted(nets.get(net.name.as_str())));
+        write_list(&mut out, head, nodes.iter().map(|s| s.as_str()));
+    }
+
+    writeln!(out, "$END").unwrap();
+
+    let renamed = NameMap {
+        ref_des: refs.renamed,
+        nets: nets.renamed,
+        footprints: package_names.renamed,
+        values: values.renamed,
+        pins: pins.renamed,
+    };
+
+    (out, renamed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{component_mut, node_mut};
+
+    #[test]
+    fn can_export_allegro() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let (netlist, renamed) = to_allegro(&netlist);
+
+        assert!(renamed.is_empty());
+
+        let packages = netlist
+            .split_once("$PACKAGES\n")
+            .and_then(|(_, rest)| rest.split_once("$NETS\n"))
+            .unwrap()
+            .0;
+        assert_eq!(
+            packages,
+            "\
+'PinHeader_1x06_P2.54mm_Vertical' ! 'Conn_01x06_Pin' ; J1
+'$noname' ! 'R' ; R1
+'SOT-23-5_HandSoldering' ! '74LVC1G00' ; U1 U2
+"
+        );
+        assert!(netlist.contains("'Net-(U1-Out)' ; U1.4 U2.1\n"));
+        assert!(netlist.ends_with("$END\n"));
+    }
+
+    #[test]
+    fn illegal_packages_values_and_pins_are_renamed() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        let r1 = component_mut(&mut netlist, "R1");
+        r1.value = "4'7k".into();
+        r1.footprint = Some("Resistor_SMD:R_0'603".into());
+        node_mut(&mut netlist, "U1", "4").num = "4+".into();
+        let (netlist, renamed) = to_allegro(&netlist);
+
+        assert_eq!(
+            renamed.footprints,
+            vec![("R_0'603".to_owned(), "R_0_603".to_owned())]
+        );
+        assert_eq!(renamed.values, vec![("4'7k".to_owned(), "4_7k".to_owned())]);
+        assert_eq!(renamed.pins, vec![("4+".to_owned(), "4_".to_owned())]);
+        assert!(netlist.contains("'R_0_603' ! '4_7k' ; R1\n"));
+        assert!(netlist.contains("'Net-(U1-Out)' ; U1.4_ U2.1\n"));
+    }
+
+    #[test]
+    fn long_lines_are_continued() {
+        let mut out = String::new();
+        let items: Vec<String> = (1..=20).map(|i| format!("U{i}.1")).collect();
+        write_list(
+            &mut out,
+            "'N' ;".to_owned(),
+            items.iter().map(|s| s.as_str()),
+        );
+
+        assert!(out.lines().count() > 1);
+        assert!(out.lines().all(|line| line.len() <= MAX_LINE_LEN + 1));
+        assert_eq!(out.matches(',').count(), out.lines().count() - 1);
+    }
+}
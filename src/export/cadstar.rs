@@ -0,0 +1,142 @@
+use std::fmt::Write;
+
+use super::{board_netlist, footprint, Mangler, NameMap};
+use crate::NetList;
+
+fn legal_ref_des(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn legal_net_name(c: char) -> bool {
+    c.is_ascii_graphic() && c != '"'
+}
+
+fn legal_footprint(c: char) -> bool {
+    !c.is_control() && c != '"'
+}
+
+/// Generate a Cadstar `.frp` netlist
+///
+/// Nets with a single node are left out, since Cadstar has no use for them. Reference designators and net names
+/// which are illegal in Cadstar are renamed, as are footprints containing quotes, and the renamed names are returned
+/// together with the netlist.
+pub fn to_cadstar(netlist: &NetList<'_>) -> (String, NameMap) {
+    let netlist = &board_netlist(netlist);
+    let refs = Mangler::new(
+        netlist.components.iter().map(|comp| comp.ref_des.as_str()),
+        legal_ref_des,
+        usize::MAX,
+    );
+    let nets = Mangler::new(
+        netlist.nets.iter().map(|net| net.name.as_str()),
+        legal_net_name,
+        usize::MAX,
+    );
+    let footprints = Mangler::new(
+        netlist.components.iter().map(footprint),
+        legal_footprint,
+        usize::MAX,
+    );
+
+    let mut out = String::new();
+
+    writeln!(out, ".HEA").unwrap();
+    writeln!(out, ".TIM {}", netlist.design.date.unwrap_or_default()).unwrap();
+    writeln!(
+        out,
+        ".APP \"{}\"",
+        netlist.design.tool.unwrap_or("Eeschema")
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+
+    for comp in netlist.components.iter() {
+        writeln!(
+            out,
+            ".ADD_COM     {}     \"{}\"     \"{}\"",
+            refs.get(comp.ref_des.as_str()),
+            comp.value.as_str().replace([' ', '"'], "_"),
+            footprints.get(footprint(comp))
+        )
+        .unwrap();
+    }
+    writeln!(out).unwrap();
+
+    for net in netlist.nets.iter() {
+        if net.nodes.len() < 2 {
+            continue;
+        }
+        for (i, node) in net.nodes.iter().enumerate() {
+            let ref_des = refs.get(node.ref_des.as_str());
+            let num: String = node.num.as_str().chars().take(4).collect();
+            match i {
+                0 => writeln!(
+                    out,
+                    ".ADD_TER   {ref_des}   {num}     \"{}\"",
+                    nets.get(net.name.as_str())
+                ),
+                1 => writeln!(out, ".TER       {ref_des}   {num}"),
+                _ => writeln!(out, "           {ref_des}   {num}"),
+            }
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+
+    writeln!(out, ".END").unwrap();
+
+    let renamed = NameMap {
+        ref_des: refs.renamed,
+        nets: nets.renamed,
+        footprints: footprints.renamed,
+        ..Default::default()
+    };
+
+    (out, renamed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::rename_net;
+
+    #[test]
+    fn can_export_cadstar() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let (frp, renamed) = to_cadstar(&netlist);
+
+        assert!(renamed.is_empty());
+        assert!(frp.starts_with(".HEA\n.TIM Tue Jan  2 19:52:07 2024\n.APP \"Eeschema 7.0.7\"\n"));
+        assert!(frp.contains(".ADD_COM     R1     \"R\"     \"$noname\"\n"));
+        assert!(frp.contains(
+            ".ADD_TER   J1   1     \"VCC\"\n.TER       R1   1\n           U1   5\n           U2   5\n"
+        ));
+        assert!(frp.ends_with(".END\n"));
+    }
+
+    #[test]
+    fn illegal_names_are_renamed() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        rename_net(&mut netlist, "/A", "/A B");
+        let (frp, renamed) = to_cadstar(&netlist);
+
+        assert_eq!(renamed.nets, vec![("/A B".to_owned(), "/A_B".to_owned())]);
+        assert!(frp.contains(".ADD_TER   J1   2     \"/A_B\"\n"));
+    }
+
+    #[test]
+    fn footprints_with_quotes_are_renamed() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        netlist.components[0].footprint = Some("Conn:1\"x6".into());
+        let (frp, renamed) = to_cadstar(&netlist);
+
+        assert_eq!(
+            renamed.footprints,
+            vec![("Conn:1\"x6".to_owned(), "Conn:1_x6".to_owned())]
+        );
+        assert!(frp.contains(".ADD_COM     J1     \"Conn_01x06_Pin\"     \"Conn:1_x6\"\n"));
+    }
+}
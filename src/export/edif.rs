@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use super::board_netlist;
+use crate::{NetList, PartId, PinNum, PinType};
+
+/// Library holding one cell per part
+const PARTS_LIBRARY: &str = "parts";
+
+/// Library holding the design itself
+const DESIGN_LIBRARY: &str = "design";
+
+/// The name of the design when none is given
+const DEFAULT_DESIGN: &str = "top";
+
+/// Quote a string, escaping the characters EDIF does not allow in strings
+fn string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' | '%' => write!(out, "%{}%", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Assign unique EDIF identifiers within a scope
+///
+/// An EDIF identifier consists of letters, digits and `_`, and must start with a letter unless prefixed by `&`.
+/// Identifiers are case insensitive. Names which are not legal identifiers are written as `(rename ident "name")`,
+/// while a name which only needs the `&`, such as `&4` for `4`, is written as the identifier alone.
+#[derive(Debug, Default)]
+struct Identifiers {
+    names: HashMap<String, String>,
+    used: HashSet<String>,
+}
+
+impl Identifiers {
+    fn get(&mut self, name: &str) -> String {
+        if let Some(ident) = self.names.get(name) {
+            return ident.clone();
+        }
+
+        let mut base: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if base.is_empty() {
+            base.push('_');
+        }
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            base.insert(0, '&');
+        }
+
+        let mut ident = base.clone();
+        let mut n = 1;
+        while !self.used.insert(ident.to_ascii_lowercase()) {
+            n += 1;
+            ident = format!("{base}_{n}");
+        }
+
+        self.names.insert(name.to_owned(), ident.clone());
+        ident
+    }
+
+    /// The identifier for a name, with a `rename` if the name is not a legal identifier
+    fn name(&mut self, name: &str) -> String {
+        let ident = self.get(name);
+        if ident.strip_prefix('&').unwrap_or(&ident) == name {
+            ident
+        } else {
+            format!("(rename {ident} {})", string(name))
+        }
+    }
+}
+
+fn direction(typ: PinType) -> &'static str {
+    match typ {
+        PinType::Input | PinType::PowerInput => "INPUT",
+        PinType::Output
+        | PinType::TriState
+        | PinType::PowerOutput
+        | PinType::OpenCollector
+        | PinType::OpenEmitter => "OUTPUT",
+        _ => "INOUT",
+    }
+}
+
+/// Parse a date like `Tue Jan  2 19:52:07 2024` into the fields of an EDIF `timeStamp`
+fn time_stamp(date: &str) -> Option<[u32; 6]> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let fields: Vec<&str> = date.split_whitespace().collect();
+    let [_, month, day, time, year] = fields[..] else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+    let mut time = time.split(':').map(|s| s.parse::<u32>());
+    let (h, m, s) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    Some([year.parse().ok()?, month, day.parse().ok()?, h, m, s])
+}
+
+fn cell_name(part_id: PartId<'_>) -> String {
+    format!("{}:{}", part_id.lib, part_id.part)
+}
+
+/// Generate an EDIF 2 0 0 netlist
+///
+/// Every part becomes a cell in the `parts` library, with a port for each pin. The direction of the port is derived
+/// from the pin type. The design is a single cell with an instance per component and a net per net. Names which are
+/// not legal EDIF identifiers, such as `/A` or `Net-(U1-Out)`, are renamed. An empty design name is replaced by
+/// `top`. Pins which appear in the nets but not in their part are added to the interface of the part's cell, with the
+/// direction of their node.
+pub fn to_edif(netlist: &NetList<'_>, design_name: &str) -> String {
+    let netlist = &board_netlist(netlist);
+    let design_name = match design_name {
+        "" => DEFAULT_DESIGN,
+        name => name,
+    };
+    let mut out = String::new();
+    let mut top = Identifiers::default();
+    let design = top.name(design_name);
+    let design_ident = top.get(design_name);
+
+    writeln!(out, "(edif {design}").unwrap();
+    writeln!(out, "  (edifVersion 2 0 0)").unwrap();
+    writeln!(out, "  (edifLevel 0)").unwrap();
+    writeln!(out, "  (keywordMap (keywordLevel 0))").unwrap();
+    writeln!(out, "  (status").unwrap();
+    writeln!(out, "    (written").unwrap();
+    if let Some([year, month, day, h, m, s]) = netlist.design.date.and_then(time_stamp) {
+        writeln!(out, "      (timeStamp {year} {month} {day} {h} {m} {s})").unwrap();
+    }
+    if let Some(tool) = netlist.design.tool {
+        writeln!(out, "      (program {})", string(tool)).unwrap();
+    }
+    writeln!(out, "      (comment \"Generated by kicad_netlist\")))").unwrap();
+
+    let mut cells = Identifiers::default();
+    let mut ports: HashMap<String, Identifiers> = HashMap::new();
+
+    writeln!(out, "  (library {PARTS_LIBRARY}").unwrap();
+    writeln!(out, "    (edifLevel 0)").unwrap();
+    writeln!(out, "    (technology (numberDefinition))").unwrap();
+    for part in netlist.parts.iter() {
+        let cell = cell_name(part.part_id);
+        writeln!(out, "    (cell {}", cells.name(&cell)).unwrap();
+        writeln!(out, "      (cellType GENERIC)").unwrap();
+        writeln!(out, "      (view netlist").unwrap();
+        writeln!(out, "        (viewType NETLIST)").unwrap();
+        write!(out, "        (interface").unwrap();
+        let part_ports = ports.entry(cell).or_default();
+        let mut pins: Vec<(PinNum<'_>, PinType)> =
+            part.pins.iter().map(|pin| (pin.num, pin.typ)).collect();
+        for node in netlist.nets.iter().flat_map(|net| net.nodes.iter()) {
+            let declared = pins.iter().any(|(num, _)| *num == node.num);
+            let of_part = netlist
+                .find_component(node.ref_des)
+                .is_some_and(|comp| comp.part_id == part.part_id);
+            if of_part && !declared {
+                pins.push((node.num, node.typ));
+            }
+        }
+        for (num, typ) in pins {
+            write!(
+                out,
+                "\n          (port {} (direction {}))",
+                part_ports.name(num.as_str()),
+                direction(typ)
+            )
+            .unwrap();
+        }
+        writeln!(out, ")))").unwrap();
+    }
+    writeln!(out, "  )").unwrap();
+
+    let mut instances = Identifiers::default();
+    let mut nets = Identifiers::default();
+
+    writeln!(out, "  (library {DESIGN_LIBRARY}").unwrap();
+    writeln!(out, "    (edifLevel 0)").unwrap();
+    writeln!(out, "    (technology (numberDefinition))").unwrap();
+    writeln!(out, "    (cell {design_ident}").unwrap();
+    writeln!(out, "      (cellType GENERIC)").unwrap();
+    writeln!(out, "      (view netlist").unwrap();
+    writeln!(out, "        (viewType NETLIST)").unwrap();
+    writeln!(out, "        (interface)").unwrap();
+    writeln!(out, "        (contents").unwrap();
+
+    for comp in netlist.components.iter() {
+        writeln!(
+            out,
+            "          (instance {}",
+            instances.name(comp.ref_des.as_str())
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "            (viewRef netlist (cellRef {} (libraryRef {PARTS_LIBRARY})))",
+            cells.get(&cell_name(comp.part_id))
+        )
+        .unwrap();
+        write!(
+            out,
+            "            (property Value (string {}))",
+            string(comp.value.as_str())
+        )
+        .unwrap();
+        if let Some(footprint) = comp.footprint {
+            write!(
+                out,
+                "\n            (property Footprint (string {}))",
+                string(footprint.as_str())
+            )
+            .unwrap();
+        }
+        writeln!(out, ")").unwrap();
+    }
+
+    for net in netlist.nets.iter() {
+        writeln!(out, "          (net {}", nets.name(net.name.as_str())).unwrap();
+        write!(out, "            (joined").unwrap();
+        for node in net.nodes.iter() {
+            let Some(comp) = netlist.find_component(node.ref_des) else {
+                continue;
+            };
+            let port = ports
+                .get_mut(&cell_name(comp.part_id))
+                .map(|part_ports| part_ports.get(node.num.as_str()));
+            let Some(port) = port else {
+                continue;
+            };
+            write!(
+                out,
+                "\n              (portRef {port} (instanceRef {}))",
+                instances.get(node.ref_des.as_str())
+            )
+            .unwrap();
+        }
+        writeln!(out, "))").unwrap();
+    }
+
+    writeln!(out, "        ))))").unwrap();
+    writeln!(
+        out,
+        "  (design {design_ident} (cellRef {design_ident} (libraryRef {DESIGN_LIBRARY}))))"
+    )
+    .unwrap();
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sexpr::SExpr;
+
+    #[test]
+    fn can_export_edif() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let edif = to_edif(&netlist, "kvt");
+
+        // The output should at least be a well formed s-expression
+        let root = SExpr::try_from(edif.as_str()).unwrap();
+        assert_eq!(root.label(), Some("edif"));
+        assert_eq!(root.children("library").count(), 2);
+
+        assert!(edif.contains("(timeStamp 2024 1 2 19 52 7)"));
+        assert!(edif.contains("(cell (rename &74xGxx_74LVC1G00 \"74xGxx:74LVC1G00\")"));
+        assert!(edif.contains("(port &4 (direction OUTPUT))"));
+        assert!(edif.contains(
+            "(net (rename Net__U1_Out_ \"Net-(U1-Out)\")\n            (joined\n              \
+             (portRef &4 (instanceRef U1))\n              (portRef &1 (instanceRef U2))))"
+        ));
+        assert!(edif.contains("(net (rename &_A \"/A\")"));
+    }
+
+    #[test]
+    fn identifiers_are_unique_ignoring_case() {
+        let mut idents = Identifiers::default();
+        assert_eq!(idents.name("GND"), "GND");
+        assert_eq!(idents.name("gnd"), "(rename gnd_2 \"gnd\")");
+        assert_eq!(idents.name("GND"), "GND");
+        assert_eq!(idents.name("1"), "&1");
+        assert_eq!(idents.name("1A"), "&1A");
+        assert_eq!(idents.name("+5V"), "(rename &_5V \"+5V\")");
+        assert_eq!(idents.name(""), "(rename &_ \"\")");
+    }
+
+    #[test]
+    fn empty_design_name_is_replaced() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let edif = to_edif(&netlist, "");
+
+        assert!(edif.starts_with("(edif top\n"));
+        assert!(edif.contains("(design top (cellRef top (libraryRef design))))"));
+    }
+
+    #[test]
+    fn undeclared_pins_are_added_to_the_interface() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        let mut node = netlist.nets[0].nodes[1].clone();
+        assert_eq!(node.ref_des, "U1".into());
+        node.num = "6".into();
+        netlist.nets[0].nodes.push(node);
+        let edif = to_edif(&netlist, "kvt");
+
+        assert!(
+            edif.contains("(port &5 (direction INPUT))\n          (port &6 (direction INPUT)))))")
+        );
+        assert!(edif.contains("(portRef &6 (instanceRef U1))"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt::Write;
+
+use super::{board_netlist, footprint};
+use crate::natural::natural_cmp;
+use crate::NetList;
+
+/// Generate an OrcadPCB2 netlist
+///
+/// The output matches what Eeschema writes for the same design, except that the date is taken from the netlist
+/// rather than the current time. Spaces in footprints, values and net names are replaced by `_`, and pin numbers
+/// are limited to four characters.
+pub fn to_orcad_pcb2(netlist: &NetList<'_>) -> String {
+    let netlist = &board_netlist(netlist);
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "( {{ EESchema Netlist Version 1.1 created  {} }}",
+        netlist.design.date.unwrap_or_default()
+    )
+    .unwrap();
+
+    for comp in netlist.components.iter() {
+        let path = format!(
+            "{}{}",
+            comp.sheet_path.map(|path| path.tstamps).unwrap_or("/"),
+            comp.tstamp.unwrap_or_default()
+        );
+        let footprint = footprint(comp).replace(' ', "_");
+        let value = comp.value.as_str().replace(' ', "_");
+
+        writeln!(out, " ( {path} {footprint}  {} {value}", comp.ref_des).unwrap();
+
+        let mut pins: Vec<_> = comp.pins.iter().collect();
+        pins.sort_by(|a, b| natural_cmp(a.num.as_str(), b.num.as_str()));
+
+        for pin in pins {
+            if pin.num.as_str().is_empty() {
+                continue;
+            }
+            let num: String = pin.num.as_str().chars().take(4).collect();
+            let net = pin.net.as_str().replace(' ', "_");
+            writeln!(out, "  ( {num:>4} {net} )").unwrap();
+        }
+
+        writeln!(out, " )").unwrap();
+    }
+
+    writeln!(out, ")\n*").unwrap();
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_export_orcad_pcb2() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+
+        let expected = "\
+( { EESchema Netlist Version 1.1 created  Tue Jan  2 19:52:07 2024 }
+ ( /73417a21-9c42-4702-9832-ec63427d336d Connector_PinHeader_2.54mm:PinHeader_1x06_P2.54mm_Vertical  J1 Conn_01x06_Pin
+  (    1 VCC )
+  (    2 /A )
+  (    3 /B )
+  (    4 GND )
+  (    5 /OUT )
+  (    6 GND )
+ )
+ ( /7b4f9616-ccd3-4604-b0c4-be98584c5a43 $noname  R1 R
+  (    1 VCC )
+  (    2 Net-(U2-B) )
+ )
+ ( /504a4355-a118-43c9-a7b5-0dbe2c3c67da Package_TO_SOT_SMD:SOT-23-5_HandSoldering  U1 74LVC1G00
+  (    1 /A )
+  (    2 /B )
+  (    3 GND )
+  (    4 Net-(U1-Out) )
+  (    5 VCC )
+ )
+ ( /d562bc2c-394e-4a47-a0fe-317a9072a6c7 Package_TO_SOT_SMD:SOT-23-5_HandSoldering  U2 74LVC1G00
+  (    1 Net-(U1-Out) )
+  (    2 Net-(U2-B) )
+  (    3 GND )
+  (    4 /OUT )
+  (    5 VCC )
+ )
+)
+*
+";
+        assert_eq!(to_orcad_pcb2(&netlist), expected);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use super::board_netlist;
+use crate::NetList;
+
+/// Longest signal line written before the nodes are continued on the next line
+const MAX_LINE_LEN: usize = 80;
+
+/// PADS names can not contain spaces
+fn name(s: &str) -> String {
+    s.replace(' ', "_")
+}
+
+/// Generate a PADS ASCII netlist
+///
+/// Parts are written as `REF VALUE@DECAL`, where the decal is the footprint name without the library. Components
+/// without a footprint are written with the value only. Long signal lines are wrapped.
+pub fn to_pads(netlist: &NetList<'_>) -> String {
+    let netlist = &board_netlist(netlist);
+    let mut out = String::new();
+
+    writeln!(out, "*PADS-PCB*").unwrap();
+    writeln!(out, "*PART*").unwrap();
+
+    for comp in netlist.components.iter() {
+        let decal = comp.footprint.map(|footprint| footprint.name());
+        match decal {
+            Some(decal) if !decal.is_empty() => writeln!(
+                out,
+                "{} {}@{}",
+                name(comp.ref_des.as_str()),
+                name(comp.value.as_str()),
+                name(decal)
+            ),
+            _ => writeln!(
+                out,
+                "{} {}",
+                name(comp.ref_des.as_str()),
+                name(comp.value.as_str())
+            ),
+        }
+        .unwrap();
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "*NET*").unwrap();
+
+    for net in netlist.nets.iter() {
+        writeln!(out, "*SIGNAL* {}", name(net.name.as_str())).unwrap();
+
+        let mut line = String::new();
+        for node in net.nodes.iter() {
+            let node = format!(
+                "{}.{}",
+                name(node.ref_des.as_str()),
+                name(node.num.as_str())
+            );
+            if !line.is_empty() && line.len() + node.len() + 1 > MAX_LINE_LEN {
+                writeln!(out, "{line}").unwrap();
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&node);
+        }
+        if !line.is_empty() {
+            writeln!(out, "{line}").unwrap();
+        }
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "*END*").unwrap();
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NetNode, PinType};
+
+    #[test]
+    fn can_export_pads() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+
+        let expected = "\
+*PADS-PCB*
+*PART*
+J1 Conn_01x06_Pin@PinHeader_1x06_P2.54mm_Vertical
+R1 R
+U1 74LVC1G00@SOT-23-5_HandSoldering
+U2 74LVC1G00@SOT-23-5_HandSoldering
+
+*NET*
+*SIGNAL* /A
+J1.2 U1.1
+*SIGNAL* /B
+J1.3 U1.2
+*SIGNAL* /OUT
+J1.5 U2.4
+*SIGNAL* GND
+J1.4 J1.6 U1.3 U2.3
+*SIGNAL* Net-(U1-Out)
+U1.4 U2.1
+*SIGNAL* Net-(U2-B)
+R1.2 U2.2
+*SIGNAL* VCC
+J1.1 R1.1 U1.5 U2.5
+
+*END*
+";
+        assert_eq!(to_pads(&netlist), expected);
+    }
+
+    #[test]
+    fn long_signal_lines_are_wrapped() {
+        let input = test_data!("kvt.net");
+        let refs: Vec<String> = (1..=30).map(|i| format!("R{i}")).collect();
+        let mut netlist = NetList::parse(&input).unwrap();
+        let vcc = netlist.nets.iter_mut().find(|net| net.name == "VCC".into());
+        vcc.unwrap()
+            .nodes
+            .extend(refs.iter().map(|ref_des| NetNode {
+                ref_des: ref_des.into(),
+                num: "1".into(),
+                function: None,
+                typ: PinType::Passive,
+            }));
+        let pads = to_pads(&netlist);
+
+        let vcc: Vec<&str> = pads
+            .split("*SIGNAL* VCC\n")
+            .nth(1)
+            .unwrap()
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .collect();
+        assert!(vcc.len() > 1);
+        assert!(vcc.iter().all(|line| line.len() <= MAX_LINE_LEN));
+        assert_eq!(vcc.join(" ").split(' ').count(), 34);
+    }
+}
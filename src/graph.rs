@@ -0,0 +1,450 @@
+//! A graph view of a netlist
+//!
+//! Components and nets are the nodes of the graph, and every pin is an edge between its component and its net. Some
+//! queries can treat two-terminal passives, such as resistors and capacitors, as part of the wiring between their two
+//! nets, which is selected with [`Traversal`].
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{Component, NetList, NetName, PinNum, RefDes};
+
+/// A node of the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Node<'a> {
+    Component(RefDes<'a>),
+    Net(NetName<'a>),
+}
+
+/// A pin of a component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PinRef<'a> {
+    pub ref_des: RefDes<'a>,
+    pub num: PinNum<'a>,
+}
+
+/// How to treat two-terminal passives while walking the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Traversal {
+    /// Stop at the passive, so only the net itself is visited
+    StopAtPassives,
+    /// Continue to the net on the other side of the passive
+    ThroughPassives,
+}
+
+/// A set of components and nets which are connected to each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Island<'a> {
+    pub components: Vec<RefDes<'a>>,
+    pub nets: Vec<NetName<'a>>,
+}
+
+/// Whether a component is a resistor, capacitor or inductor with two pins
+pub fn is_two_terminal_passive(comp: &Component<'_>) -> bool {
+    let prefix = comp
+        .ref_des
+        .as_str()
+        .trim_end_matches(|c: char| c.is_ascii_digit());
+    let mut nums: Vec<&str> = comp.pins.iter().map(|pin| pin.num.as_str()).collect();
+    nums.sort_unstable();
+    nums.dedup();
+    matches!(prefix, "R" | "C" | "L") && nums.len() == 2
+}
+
+/// Adjacency lists for the components and nets of a netlist
+#[derive(Debug, Clone)]
+pub struct Graph<'n, 'a> {
+    netlist: &'n NetList<'a>,
+    components: HashMap<&'a str, usize>,
+    nets: HashMap<&'a str, usize>,
+    /// The pins of each component, with the index of their net
+    comp_pins: Vec<Vec<(PinNum<'a>, usize)>>,
+    /// The pins on each net, with the index of their component
+    net_pins: Vec<Vec<(usize, PinNum<'a>)>>,
+    passive: Vec<bool>,
+}
+
+impl<'n, 'a> Graph<'n, 'a> {
+    /// Build the graph, ignoring nodes which refer to unknown components
+    pub fn new(netlist: &'n NetList<'a>) -> Self {
+        let components: HashMap<_, _> = netlist
+            .components
+            .iter()
+            .enumerate()
+            .map(|(i, comp)| (comp.ref_des.as_str(), i))
+            .collect();
+        let nets = netlist
+            .nets
+            .iter()
+            .enumerate()
+            .map(|(i, net)| (net.name.as_str(), i))
+            .collect();
+
+        let mut comp_pins = vec![vec![]; netlist.components.len()];
+        let mut net_pins = vec![vec![]; netlist.nets.len()];
+        for (n, net) in netlist.nets.iter().enumerate() {
+            for node in net.nodes.iter() {
+                if let Some(&c) = components.get(node.ref_des.as_str()) {
+                    comp_pins[c].push((node.num, n));
+                    net_pins[n].push((c, node.num));
+                }
+            }
+        }
+        let passive = netlist
+            .components
+            .iter()
+            .map(is_two_terminal_passive)
+            .collect();
+
+        Graph {
+            netlist,
+            components,
+            nets,
+            comp_pins,
+            net_pins,
+            passive,
+        }
+    }
+
+    fn ref_des(&self, c: usize) -> RefDes<'a> {
+        self.netlist.components[c].ref_des
+    }
+
+    fn net_name(&self, n: usize) -> NetName<'a> {
+        self.netlist.nets[n].name
+    }
+
+    /// The net a pin is connected to
+    pub fn net_of(&self, ref_des: RefDes<'_>, num: PinNum<'_>) -> Option<NetName<'a>> {
+        let &c = self.components.get(ref_des.as_str())?;
+        self.comp_pins[c]
+            .iter()
+            .find(|(pin, _)| pin.as_str() == num.as_str())
+            .map(|&(_, n)| self.net_name(n))
+    }
+
+    /// The pins of a component with their nets
+    pub fn pins(
+        &self,
+        ref_des: RefDes<'_>,
+    ) -> impl Iterator<Item = (PinNum<'a>, NetName<'a>)> + '_ {
+        let pins = match self.components.get(ref_des.as_str()) {
+            Some(&c) => self.comp_pins[c].as_slice(),
+            None => &[],
+        };
+        pins.iter().map(|&(num, n)| (num, self.net_name(n)))
+    }
+
+    /// The pins connected to a net
+    pub fn net_pins(&self, net: NetName<'_>) -> impl Iterator<Item = PinRef<'a>> + '_ {
+        let pins = match self.nets.get(net.as_str()) {
+            Some(&n) => self.net_pins[n].as_slice(),
+            None => &[],
+        };
+        pins.iter().map(|&(c, num)| PinRef {
+            ref_des: self.ref_des(c),
+            num,
+        })
+    }
+
+    /// The other pins on the net of a pin
+    pub fn pin_neighbours(&self, ref_des: RefDes<'_>, num: PinNum<'_>) -> Vec<PinRef<'a>> {
+        let Some(net) = self.net_of(ref_des, num) else {
+            return vec![];
+        };
+        self.net_pins(net)
+            .filter(|pin| {
+                pin.ref_des.as_str() != ref_des.as_str() || pin.num.as_str() != num.as_str()
+            })
+            .collect()
+    }
+
+    /// The other components which share a net with a component
+    pub fn neighbours(&self, ref_des: RefDes<'_>) -> Vec<RefDes<'a>> {
+        let Some(&start) = self.components.get(ref_des.as_str()) else {
+            return vec![];
+        };
+        let mut found: Vec<usize> = vec![];
+        for &(_, n) in self.comp_pins[start].iter() {
+            for &(c, _) in self.net_pins[n].iter() {
+                if c != start && !found.contains(&c) {
+                    found.push(c);
+                }
+            }
+        }
+        found.sort_unstable();
+        found.into_iter().map(|c| self.ref_des(c)).collect()
+    }
+
+    /// Visit every net reachable from a net
+    fn reach(&self, start: usize, traversal: Traversal) -> Vec<usize> {
+        let mut seen = vec![false; self.net_pins.len()];
+        let mut queue = VecDeque::from([start]);
+        let mut order = vec![];
+        seen[start] = true;
+        while let Some(n) = queue.pop_front() {
+            order.push(n);
+            if traversal == Traversal::StopAtPassives {
+                continue;
+            }
+            for &(c, _) in self.net_pins[n].iter().filter(|(c, _)| self.passive[*c]) {
+                for &(_, next) in self.comp_pins[c].iter() {
+                    if !seen[next] {
+                        seen[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    /// The nets reachable from a net, starting with the net itself
+    pub fn reachable_nets(&self, net: NetName<'_>, traversal: Traversal) -> Vec<NetName<'a>> {
+        match self.nets.get(net.as_str()) {
+            Some(&n) => self
+                .reach(n, traversal)
+                .into_iter()
+                .map(|n| self.net_name(n))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// The pins on the nets reachable from a pin, leaving out the pin itself
+    ///
+    /// When walking through passives, the pins of the passives are included.
+    pub fn connected(
+        &self,
+        ref_des: RefDes<'_>,
+        num: PinNum<'_>,
+        traversal: Traversal,
+    ) -> Vec<PinRef<'a>> {
+        let Some(net) = self.net_of(ref_des, num) else {
+            return vec![];
+        };
+        let start = self.nets[net.as_str()];
+        self.reach(start, traversal)
+            .into_iter()
+            .flat_map(|n| self.net_pins[n].iter())
+            .filter(|(c, pin)| {
+                self.ref_des(*c).as_str() != ref_des.as_str() || pin.as_str() != num.as_str()
+            })
+            .map(|&(c, num)| PinRef {
+                ref_des: self.ref_des(c),
+                num,
+            })
+            .collect()
+    }
+
+    /// The shortest path between two components, passing only through two-terminal passives
+    ///
+    /// The path alternates between components and nets, starting with `from` and ending with `to`.
+    pub fn shortest_path(&self, from: RefDes<'_>, to: RefDes<'_>) -> Option<Vec<Node<'a>>> {
+        let &start = self.components.get(from.as_str())?;
+        let &end = self.components.get(to.as_str())?;
+        if start == end {
+            return Some(vec![Node::Component(self.ref_des(start))]);
+        }
+
+        // Breadth first search over components, remembering the net and component each was reached from
+        let mut previous: Vec<Option<(usize, usize)>> = vec![None; self.comp_pins.len()];
+        let mut queue = VecDeque::from([start]);
+        while let Some(c) = queue.pop_front() {
+            if c != start && !self.passive[c] {
+                continue;
+            }
+            for &(_, n) in self.comp_pins[c].iter() {
+                for &(next, _) in self.net_pins[n].iter() {
+                    if next != start && previous[next].is_none() {
+                        previous[next] = Some((n, c));
+                        queue.push_back(next);
+                    }
+                }
+            }
+            if previous[end].is_some() {
+                break;
+            }
+        }
+
+        let mut path = vec![Node::Component(self.ref_des(end))];
+        let mut c = end;
+        while let Some((n, prev)) = previous[c] {
+            path.push(Node::Net(self.net_name(n)));
+            path.push(Node::Component(self.ref_des(prev)));
+            c = prev;
+        }
+        if c != start {
+            return None;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Split the netlist into sets of components and nets which are connected to each other
+    ///
+    /// Components without any connected pin form islands of their own.
+    pub fn islands(&self) -> Vec<Island<'a>> {
+        let mut comp_island = vec![None; self.comp_pins.len()];
+        let mut net_island = vec![None; self.net_pins.len()];
+        let mut islands = vec![];
+
+        for start in 0..self.comp_pins.len() {
+            if comp_island[start].is_some() {
+                continue;
+            }
+            let index = islands.len();
+            let mut island = Island {
+                components: vec![],
+                nets: vec![],
+            };
+            comp_island[start] = Some(index);
+            let mut queue = VecDeque::from([start]);
+            while let Some(c) = queue.pop_front() {
+                island.components.push(self.ref_des(c));
+                for &(_, n) in self.comp_pins[c].iter() {
+                    if net_island[n].is_some() {
+                        continue;
+                    }
+                    net_island[n] = Some(index);
+                    island.nets.push(self.net_name(n));
+                    for &(next, _) in self.net_pins[n].iter() {
+                        if comp_island[next].is_none() {
+                            comp_island[next] = Some(index);
+                            queue.push_back(next);
+                        }
+                    }
+                }
+            }
+            islands.push(island);
+        }
+        islands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin<'a>(ref_des: &'a str, num: &'a str) -> PinRef<'a> {
+        PinRef {
+            ref_des: ref_des.into(),
+            num: num.into(),
+        }
+    }
+
+    #[test]
+    fn can_find_neighbours() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let graph = Graph::new(&netlist);
+
+        assert_eq!(
+            graph.net_of("U1".into(), "4".into()),
+            Some("Net-(U1-Out)".into())
+        );
+        assert_eq!(
+            graph.pin_neighbours("U1".into(), "4".into()),
+            vec![pin("U2", "1")]
+        );
+        assert_eq!(
+            graph.neighbours("R1".into()),
+            vec!["J1".into(), "U1".into(), "U2".into()]
+        );
+        assert!(is_two_terminal_passive(
+            netlist.find_component("R1".into()).unwrap()
+        ));
+    }
+
+    #[test]
+    fn can_walk_through_passives() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let graph = Graph::new(&netlist);
+
+        assert_eq!(
+            graph.connected("U2".into(), "2".into(), Traversal::StopAtPassives),
+            vec![pin("R1", "2")]
+        );
+        assert_eq!(
+            graph.reachable_nets("Net-(U2-B)".into(), Traversal::ThroughPassives),
+            vec!["Net-(U2-B)".into(), "VCC".into()]
+        );
+        assert_eq!(
+            graph.connected("U2".into(), "2".into(), Traversal::ThroughPassives),
+            vec![
+                pin("R1", "2"),
+                pin("J1", "1"),
+                pin("R1", "1"),
+                pin("U1", "5"),
+                pin("U2", "5")
+            ]
+        );
+    }
+
+    #[test]
+    fn can_find_shortest_path() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        netlist
+            .nets
+            .retain(|net| net.name != "/OUT".into() && net.name != "GND".into());
+        netlist.nets[4]
+            .nodes
+            .retain(|node| node.ref_des != "U2".into());
+        let graph = Graph::new(&netlist);
+
+        assert_eq!(
+            graph.shortest_path("J1".into(), "U2".into()),
+            Some(vec![
+                Node::Component("J1".into()),
+                Node::Net("VCC".into()),
+                Node::Component("R1".into()),
+                Node::Net("Net-(U2-B)".into()),
+                Node::Component("U2".into()),
+            ])
+        );
+        assert_eq!(
+            graph
+                .shortest_path("U1".into(), "U2".into())
+                .map(|p| p.len()),
+            Some(3)
+        );
+        assert_eq!(graph.shortest_path("J1".into(), "X1".into()), None);
+    }
+
+    #[test]
+    fn can_find_islands() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        let graph = Graph::new(&netlist);
+        let islands = graph.islands();
+        assert_eq!(islands.len(), 1);
+        assert_eq!(islands[0].components.len(), 4);
+        assert_eq!(islands[0].nets.len(), 7);
+
+        // Without the connector and the nets of the resistor, the two gates are separate from the resistor
+        netlist.remove_component("J1".into());
+        netlist
+            .nets
+            .retain(|net| !["VCC", "GND", "Net-(U2-B)"].contains(&net.name.as_str()));
+        let graph = Graph::new(&netlist);
+        assert_eq!(
+            graph.islands(),
+            vec![
+                Island {
+                    components: vec!["R1".into()],
+                    nets: vec![]
+                },
+                Island {
+                    components: vec!["U1".into(), "U2".into()],
+                    nets: vec![
+                        "/A".into(),
+                        "/B".into(),
+                        "Net-(U1-Out)".into(),
+                        "/OUT".into()
+                    ]
+                },
+            ]
+        );
+    }
+}
@@ -1,26 +1,88 @@
 //! # Read and manipulate KiCad netlist files
 //!
 //! The netlist is parsed from a provided `str` or `String` reference, and all data is stored as references into that string.
+//!
+//! ## Serde
+//!
+//! With the `serde` feature enabled the netlist types implement `Serialize` and `Deserialize`. The schema mirrors the
+//! structs field by field: wrapper types such as [`RefDes`] are plain strings, [`PinType`] uses the same names as
+//! KiCad (`input`, `power_in`, `no_connect`, ...), and optional fields as well as `design` and component
+//! `properties` may be left out. Since strings are borrowed from the input, deserializing a string which contains
+//! escape sequences fails.
 
+/// Read a file from `resources/test` for use in the tests of any module
+#[cfg(test)]
+macro_rules! test_data {
+    ($fname:expr) => {
+        std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/resources/test/",
+            $fname
+        ))
+        .unwrap()
+    };
+}
+
+pub mod board;
+pub mod bom;
+pub mod builder;
+pub mod classify;
+pub mod decoupling;
+pub mod diff;
+pub mod equiv;
+pub mod erc;
 mod error;
+pub mod export;
+pub mod graph;
+pub mod library;
+mod names;
+mod natural;
 mod parse;
+pub mod power;
+pub mod pulls;
+pub mod quantity;
 pub mod raw;
+pub mod schematic;
 mod sexpr;
+pub mod sim;
+#[cfg(test)]
+mod test_util;
+pub mod validate;
+pub mod verilog;
 
 use std::collections::HashSet;
 
-pub use error::ParseError;
+pub use error::{BuildError, LibraryError, ParseError, SchematicError, SimError, VerilogError};
 
 /// The full netlist
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct NetList<'a> {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub design: Design<'a>,
     pub components: Vec<Component<'a>>,
     pub parts: Vec<Part<'a>>,
     pub nets: Vec<Net<'a>>,
 }
 
+/// Information about the schematic the netlist was generated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
+pub struct Design<'a> {
+    pub source: Option<&'a str>,
+    pub date: Option<&'a str>,
+    pub tool: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub company: Option<&'a str>,
+    pub rev: Option<&'a str>,
+}
+
 /// Part identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct PartId<'a> {
     pub lib: &'a str,
     pub part: &'a str,
@@ -28,6 +90,8 @@ pub struct PartId<'a> {
 
 /// General property
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct Property<'a> {
     pub name: &'a str,
     pub value: &'a str,
@@ -37,6 +101,8 @@ pub struct Property<'a> {
 macro_rules! define_pub_str_wrapper {
     ($name:ident,$doc:expr) => {
         #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(transparent))]
         #[doc = $doc]
         pub struct $name<'a>(&'a str);
 
@@ -47,8 +113,8 @@ macro_rules! define_pub_str_wrapper {
         }
 
         impl<'a> $name<'a> {
-            pub fn as_str(&self) -> &str {
-                self.0.as_ref()
+            pub fn as_str(&self) -> &'a str {
+                self.0
             }
         }
 
@@ -67,7 +133,7 @@ macro_rules! define_pub_str_wrapper {
 }
 
 define_pub_str_wrapper!(RefDes, "Reference designator");
-define_pub_str_wrapper!(PinNum, "Pin number\n\nNote that the number is a string, not an actual number, because we need to support, eg, BGA packages with pin numbers A1, A2, A3 etc.");
+define_pub_str_wrapper!(PinNum, "Pin number\n\nNote that the number is a string, not an actual number, because we need to support, eg, BGA packages with pin numbers A1, A2, A3 etc. Use [`PinNum::number`] or [`PinNum::grid`] to interpret it.");
 define_pub_str_wrapper!(PinName, "Name of pin");
 define_pub_str_wrapper!(PinFunction, "Pin function");
 define_pub_str_wrapper!(Value, "Component value");
@@ -78,17 +144,37 @@ define_pub_str_wrapper!(PartDescription, "Description");
 
 /// A component in the schematic
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct Component<'a> {
     pub ref_des: RefDes<'a>,
     pub value: Value<'a>,
     pub part_id: PartId<'a>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub properties: Vec<Property<'a>>,
+    /// User defined fields
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fields: Vec<Property<'a>>,
     pub footprint: Option<Footprint<'a>>,
     pub pins: Vec<ComponentPin<'a>>,
+    pub sheet_path: Option<SheetPath<'a>>,
+    /// The unique id of the symbol in the schematic
+    pub tstamp: Option<&'a str>,
+}
+
+/// The sheet a component is placed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
+pub struct SheetPath<'a> {
+    pub names: &'a str,
+    pub tstamps: &'a str,
 }
 
 /// The electrical type of the pin
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum PinType {
     Input,
     Output,
@@ -96,15 +182,21 @@ pub enum PinType {
     TriState,
     Passive,
     Free,
+    Unspecified,
+    #[cfg_attr(feature = "serde", serde(rename = "power_in"))]
     PowerInput,
+    #[cfg_attr(feature = "serde", serde(rename = "power_out"))]
     PowerOutput,
     OpenCollector,
     OpenEmitter,
+    #[cfg_attr(feature = "serde", serde(rename = "no_connect"))]
     Unconnected,
 }
 
 /// A pin of an individual component
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct ComponentPin<'a> {
     pub num: PinNum<'a>,
     pub name: PinName<'a>,
@@ -114,6 +206,8 @@ pub struct ComponentPin<'a> {
 
 /// A pin of a part
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct PartPin<'a> {
     pub num: PinNum<'a>,
     pub name: PinName<'a>,
@@ -122,15 +216,22 @@ pub struct PartPin<'a> {
 
 /// A part
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct Part<'a> {
     pub part_id: PartId<'a>,
     pub description: PartDescription<'a>,
+    /// Wildcard patterns for the footprints which fit the part
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub footprint_filters: Vec<&'a str>,
     pub pins: Vec<PartPin<'a>>,
     pub components: Vec<RefDes<'a>>,
 }
 
 /// A node connects a net to a pin
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct NetNode<'a> {
     pub ref_des: RefDes<'a>,
     pub num: PinNum<'a>,
@@ -140,6 +241,8 @@ pub struct NetNode<'a> {
 
 /// A net
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct Net<'a> {
     /// A unique id for the net
     pub code: NetCode<'a>,
@@ -179,7 +282,7 @@ impl<'a> NetList<'a> {
             return;
         };
 
-        let part_id = self.components[index].part_id.clone();
+        let part_id = self.components[index].part_id;
 
         self.components.remove(index);
 
@@ -202,7 +305,7 @@ impl<'a> NetList<'a> {
         let removed_part_ids: HashSet<_> =
             HashSet::from_iter(self.components.iter().filter_map(|comp| {
                 if ref_des_list.contains(&comp.ref_des) {
-                    Some(comp.part_id.clone())
+                    Some(comp.part_id)
                 } else {
                     None
                 }
@@ -230,27 +333,92 @@ impl<'a> NetList<'a> {
         }
     }
 
-    pub fn find_component(&self, ref_des: RefDes<'_>) -> Option<&Component<'_>> {
+    pub fn find_component(&self, ref_des: RefDes<'_>) -> Option<&Component<'a>> {
         self.components.iter().find(|comp| comp.ref_des == ref_des)
     }
 
-    pub fn find_part(&self, part_id: PartId<'_>) -> Option<&Part<'_>> {
+    pub fn find_part(&self, part_id: PartId<'_>) -> Option<&Part<'a>> {
         self.parts.iter().find(|p| p.part_id == part_id)
     }
 
-    pub fn find_net(&self, name: NetName<'_>) -> Option<&Net<'_>> {
+    pub fn find_net(&self, name: NetName<'_>) -> Option<&Net<'a>> {
         self.nets.iter().find(|net| net.name == name)
     }
 }
 
+impl<'a> Footprint<'a> {
+    /// The library nickname, if the footprint has one
+    pub fn lib(&self) -> Option<&'a str> {
+        self.0.split_once(':').map(|(lib, _)| lib)
+    }
+
+    /// The name of the footprint without the library
+    pub fn name(&self) -> &'a str {
+        self.0.split_once(':').map_or(self.0, |(_, name)| name)
+    }
+}
+
+impl PinType {
+    /// The name used for the pin type in KiCad netlists
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PinType::Input => "input",
+            PinType::Output => "output",
+            PinType::Bidirectional => "bidirectional",
+            PinType::TriState => "tri_state",
+            PinType::Passive => "passive",
+            PinType::Free => "free",
+            PinType::Unspecified => "unspecified",
+            PinType::PowerInput => "power_in",
+            PinType::PowerOutput => "power_out",
+            PinType::OpenCollector => "open_collector",
+            PinType::OpenEmitter => "open_emitter",
+            PinType::Unconnected => "no_connect",
+        }
+    }
+
+    /// Whether the pin drives a logic level onto its net
+    pub fn drives(&self) -> bool {
+        matches!(
+            self,
+            PinType::Output | PinType::Bidirectional | PinType::TriState
+        )
+    }
+
+    /// Whether the pin reads the logic level of its net
+    pub fn receives(&self) -> bool {
+        matches!(self, PinType::Input | PinType::Bidirectional)
+    }
+}
+
+impl std::fmt::Display for PinType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl<'a> Component<'a> {
-    pub fn find_pin(&self, num: PinNum<'_>) -> Option<&ComponentPin<'_>> {
-        self.pins.iter().find(|pin| pin.num == num).map(|pin| pin)
+    pub fn find_pin(&self, num: PinNum<'_>) -> Option<&ComponentPin<'a>> {
+        self.pins.iter().find(|pin| pin.num == num)
+    }
+
+    pub fn find_property(&self, name: &str) -> Option<&'a str> {
+        self.properties
+            .iter()
+            .find(|prop| prop.name == name)
+            .map(|prop| prop.value)
+    }
+
+    pub fn find_field(&self, name: &str) -> Option<&'a str> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.value)
     }
 }
 
 impl<'a> Net<'a> {
-    pub fn find_node(&self, ref_des: RefDes<'_>, num: PinNum<'_>) -> Option<&NetNode<'_>> {
+    pub fn find_node(&self, ref_des: RefDes<'_>, num: PinNum<'_>) -> Option<&NetNode<'a>> {
         self.nodes
             .iter()
             .find(|node| node.ref_des == ref_des && node.num == num)
@@ -261,17 +429,6 @@ impl<'a> Net<'a> {
 mod tests {
     use super::*;
 
-    macro_rules! test_data {
-        ($fname:expr) => {
-            std::fs::read_to_string(concat!(
-                env!("CARGO_MANIFEST_DIR"),
-                "/resources/test/",
-                $fname
-            ))
-            .unwrap()
-        };
-    }
-
     #[test]
     fn remove_component_works() {
         let input = test_data!("kvt.net");
@@ -303,13 +460,27 @@ mod tests {
         assert_eq!(netlist.parts.len(), 3);
         assert_eq!(netlist.nets.len(), 7);
 
-        netlist.remove_components(&vec![RefDes::from("R1"), RefDes::from("U2")]);
+        netlist.remove_components(&[RefDes::from("R1"), RefDes::from("U2")]);
 
         assert_eq!(netlist.components.len(), 2);
         assert_eq!(netlist.parts.len(), 2);
         assert_eq!(netlist.nets.len(), 6);
     }
 
+    #[test]
+    fn can_parse_pin_types() {
+        assert_eq!(PinType::try_from("power_in").unwrap(), PinType::PowerInput);
+        assert_eq!(
+            PinType::try_from("unspecified").unwrap(),
+            PinType::Unspecified
+        );
+        assert_eq!(PinType::Unspecified.as_str(), "unspecified");
+        assert_eq!(
+            PinType::try_from("input+no_connect").unwrap(),
+            PinType::Unconnected
+        );
+    }
+
     #[test]
     fn test_load_old_netlist() -> Result<(), ParseError> {
         let input = test_data!("old-vD.net");
@@ -320,4 +491,52 @@ mod tests {
             Ok(_) => panic!("Expected an error"),
         }
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn netlist_json_round_trip() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+
+        let json = serde_json::to_string(&netlist).unwrap();
+        let result: NetList = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(result, netlist);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn netlist_can_be_read_from_yaml() {
+        let input = "
+components:
+  - ref_des: R1
+    value: 10k
+    part_id: { lib: Device, part: R }
+    pins:
+      - { num: '1', name: '', typ: passive, net: VCC }
+      - { num: '2', name: '', typ: passive, net: /OUT }
+parts:
+  - part_id: { lib: Device, part: R }
+    description: Resistor
+    pins:
+      - { num: '1', name: '', typ: passive }
+      - { num: '2', name: '', typ: passive }
+    components: [R1]
+nets:
+  - code: '1'
+    name: VCC
+    nodes:
+      - { ref_des: R1, num: '1', typ: passive }
+  - code: '2'
+    name: /OUT
+    nodes:
+      - { ref_des: R1, num: '2', typ: power_in }
+";
+        let netlist: NetList = serde_yaml::from_str(input).unwrap();
+
+        assert_eq!(netlist.components[0].ref_des, RefDes::from("R1"));
+        assert_eq!(netlist.components[0].footprint, None);
+        assert_eq!(netlist.nets[1].nodes[0].typ, PinType::PowerInput);
+        assert_eq!(netlist.parts[0].components, vec![RefDes::from("R1")]);
+    }
 }
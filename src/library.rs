@@ -0,0 +1,29 @@
+//! Read KiCad footprint and symbol libraries
+//!
+//! The library files are read with the same s-expression parser as the netlist, and the parsed data borrows from the
+//! file contents just like the netlist does.
+
+mod footprint;
+mod symbol;
+
+use std::path::Path;
+
+use crate::LibraryError;
+
+pub use footprint::{check_pads, compare_pads, FootprintDef, FootprintLibraries, Pad, PadIssue};
+pub use symbol::{
+    check_parts, compare_part, AlternateFunction, PartIssue, SymbolDef, SymbolLib, SymbolLibraries,
+    SymbolPin,
+};
+
+/// Read a library file, returning `None` if it does not exist
+fn read(path: &Path) -> Result<Option<String>, LibraryError> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(LibraryError::Io {
+            path: path.to_owned(),
+            source,
+        }),
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::{hash_map::Entry, HashMap};
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use crate::sexpr::SExpr;
+use crate::{Component, Footprint, LibraryError, NetList, ParseError, PinNum, RefDes};
+
+/// A pad of a footprint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pad<'a> {
+    /// The pad number, which is empty for pads which do not connect to a pin
+    pub num: PinNum<'a>,
+    /// The kind of pad, such as `smd` or `thru_hole`
+    pub kind: &'a str,
+}
+
+/// A footprint read from a `.kicad_mod` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FootprintDef<'a> {
+    pub name: &'a str,
+    pub pads: Vec<Pad<'a>>,
+}
+
+impl<'a> FootprintDef<'a> {
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let sexpr = SExpr::try_from(input)?;
+        (&sexpr).try_into()
+    }
+
+    /// The distinct pad numbers, leaving out unnumbered pads such as mounting holes
+    pub fn pad_numbers(&self) -> Vec<PinNum<'a>> {
+        let mut nums: Vec<PinNum<'a>> = vec![];
+        for pad in self.pads.iter() {
+            if !pad.num.as_str().is_empty() && !nums.contains(&pad.num) {
+                nums.push(pad.num);
+            }
+        }
+        nums
+    }
+}
+
+impl<'a> TryFrom<&SExpr<'a>> for FootprintDef<'a> {
+    type Error = ParseError;
+
+    fn try_from(value: &SExpr<'a>) -> Result<Self, Self::Error> {
+        // Files written before KiCad 6 use `module` instead of `footprint`
+        let label = value.label().unwrap_or_default();
+        if label != "footprint" && label != "module" {
+            return Err(ParseError::UnexpectedRootLabel(label.to_owned()));
+        }
+
+        let name = value.string().ok_or(ParseError::MissingValue())?;
+        let pads = value
+            .children("pad")
+            .map(|pad| {
+                let mut strings = pad.strings();
+                let num = strings.next().ok_or(ParseError::MissingValue())?;
+                let kind = strings.next().ok_or(ParseError::MissingValue())?;
+                Ok(Pad {
+                    num: num.into(),
+                    kind,
+                })
+            })
+            .collect::<Result<_, ParseError>>()?;
+
+        Ok(FootprintDef { name, pads })
+    }
+}
+
+/// A map from library nicknames to `.pretty` directories
+#[derive(Debug, Clone, Default)]
+pub struct FootprintLibraries {
+    libs: HashMap<String, PathBuf>,
+}
+
+impl FootprintLibraries {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the `.pretty` directory of a library
+    pub fn add(&mut self, nickname: impl Into<String>, path: impl Into<PathBuf>) {
+        self.libs.insert(nickname.into(), path.into());
+    }
+
+    /// The path of the `.kicad_mod` file of the footprint, if the library is known
+    pub fn path(&self, footprint: Footprint<'_>) -> Option<PathBuf> {
+        let dir = self.libs.get(footprint.lib().unwrap_or_default())?;
+        Some(dir.join(format!("{}.kicad_mod", footprint.name())))
+    }
+
+    /// Read the `.kicad_mod` file of the footprint, returning `None` if it cannot be found
+    pub fn read(&self, footprint: Footprint<'_>) -> Result<Option<String>, LibraryError> {
+        match self.path(footprint) {
+            Some(path) => super::read(&path),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A difference between the pins of a component and the pads of its footprint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PadIssue<'a> {
+    /// The footprint of the component was not found in the libraries
+    FootprintNotFound {
+        ref_des: RefDes<'a>,
+        footprint: Footprint<'a>,
+    },
+    /// The footprint file of the component could not be parsed
+    InvalidFootprint {
+        ref_des: RefDes<'a>,
+        footprint: Footprint<'a>,
+        error: String,
+    },
+    /// A pin of the component has no pad with the same number
+    PinWithoutPad {
+        ref_des: RefDes<'a>,
+        num: PinNum<'a>,
+    },
+    /// A numbered pad of the footprint has no pin with the same number
+    PadWithoutPin { ref_des: RefDes<'a>, num: String },
+    /// The component and the footprint have different numbers of pins
+    PinCountMismatch {
+        ref_des: RefDes<'a>,
+        pins: usize,
+        pads: usize,
+    },
+}
+
+impl Display for PadIssue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PadIssue::FootprintNotFound { ref_des, footprint } => {
+                write!(f, "Footprint {footprint} of {ref_des} not found")
+            }
+            PadIssue::InvalidFootprint {
+                ref_des,
+                footprint,
+                error,
+            } => write!(f, "Footprint {footprint} of {ref_des} is invalid: {error}"),
+            PadIssue::PinWithoutPad { ref_des, num } => {
+                write!(f, "Pin {ref_des}.{num} has no pad in the footprint")
+            }
+            PadIssue::PadWithoutPin { ref_des, num } => {
+                write!(f, "Pad {num} of the footprint of {ref_des} has no pin")
+            }
+            PadIssue::PinCountMismatch {
+                ref_des,
+                pins,
+                pads,
+            } => {
+                write!(
+                    f,
+                    "{ref_des} has {pins} pins but its footprint has {pads} pads"
+                )
+            }
+        }
+    }
+}
+
+/// Compare the pins of a component with the pads of a footprint
+pub fn compare_pads<'a>(comp: &Component<'a>, footprint: &FootprintDef<'_>) -> Vec<PadIssue<'a>> {
+    let ref_des = comp.ref_des;
+    let pads = footprint.pad_numbers();
+    let mut pins: Vec<PinNum<'a>> = vec![];
+    for pin in comp.pins.iter() {
+        if !pins.contains(&pin.num) {
+            pins.push(pin.num);
+        }
+    }
+
+    let mut issues = vec![];
+    for &num in pins.iter() {
+        if !pads.iter().any(|pad| pad.as_str() == num.as_str()) {
+            issues.push(PadIssue::PinWithoutPad { ref_des, num });
+        }
+    }
+    for pad in pads.iter() {
+        if !pins.iter().any(|pin| pin.as_str() == pad.as_str()) {
+            issues.push(PadIssue::PadWithoutPin {
+                ref_des,
+                num: pad.to_string(),
+            });
+        }
+    }
+    if pins.len() != pads.len() {
+        issues.push(PadIssue::PinCountMismatch {
+            ref_des,
+            pins: pins.len(),
+            pads: pads.len(),
+        });
+    }
+    issues
+}
+
+/// Compare the pins of every component with the pads of its footprint
+///
+/// Components without a footprint are skipped. Each footprint file is only read once, and a file which cannot be
+/// parsed is reported for each component using it.
+pub fn check_pads<'a>(
+    netlist: &NetList<'a>,
+    libs: &FootprintLibraries,
+) -> Result<Vec<PadIssue<'a>>, LibraryError> {
+    let mut files: HashMap<Footprint<'a>, Option<String>> = HashMap::new();
+    for footprint in netlist.components.iter().filter_map(|comp| comp.footprint) {
+        if let Entry::Vacant(entry) = files.entry(footprint) {
+            entry.insert(libs.read(footprint)?);
+        }
+    }
+
+    let mut footprints = HashMap::new();
+    for (footprint, text) in files.iter() {
+        if let Some(text) = text {
+            footprints.insert(*footprint, FootprintDef::parse(text));
+        }
+    }
+
+    let mut issues = vec![];
+    for comp in netlist.components.iter() {
+        let Some(footprint) = comp.footprint else {
+            continue;
+        };
+        match footprints.get(&footprint) {
+            Some(Ok(def)) => issues.extend(compare_pads(comp, def)),
+            Some(Err(error)) => issues.push(PadIssue::InvalidFootprint {
+                ref_des: comp.ref_des,
+                footprint,
+                error: error.to_string(),
+            }),
+            None => issues.push(PadIssue::FootprintNotFound {
+                ref_des: comp.ref_des,
+                footprint,
+            }),
+        }
+    }
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_libs() -> FootprintLibraries {
+        let dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/resources/test/footprints"
+        ));
+        let mut libs = FootprintLibraries::new();
+        libs.add("Package_TO_SOT_SMD", dir.join("Package_TO_SOT_SMD.pretty"));
+        libs.add(
+            "Connector_PinHeader_2.54mm",
+            dir.join("Connector_PinHeader_2.54mm.pretty"),
+        );
+        libs
+    }
+
+    #[test]
+    fn can_parse_footprint() {
+        let input =
+            test_data!("footprints/Package_TO_SOT_SMD.pretty/SOT-23-5_HandSoldering.kicad_mod");
+        let footprint = FootprintDef::parse(&input).unwrap();
+
+        assert_eq!(footprint.name, "SOT-23-5_HandSoldering");
+        assert_eq!(footprint.pads.len(), 5);
+        assert_eq!(footprint.pads[0].kind, "smd");
+        assert_eq!(
+            footprint.pad_numbers(),
+            vec!["1".into(), "2".into(), "3".into(), "4".into(), "5".into()]
+        );
+    }
+
+    #[test]
+    fn can_parse_legacy_module() {
+        let input = r#"(module R_0603 (layer F.Cu)
+            (pad 1 smd rect (at -0.75 0) (size 0.8 0.8) (layers F.Cu F.Paste F.Mask))
+            (pad 2 smd rect (at 0.75 0) (size 0.8 0.8) (layers F.Cu F.Paste F.Mask))
+            (pad "" np_thru_hole circle (at 0 1) (size 1 1) (drill 1) (layers *.Cu)))"#;
+        let footprint = FootprintDef::parse(input).unwrap();
+
+        assert_eq!(footprint.name, "R_0603");
+        assert_eq!(footprint.pads.len(), 3);
+        assert_eq!(footprint.pad_numbers(), vec!["1".into(), "2".into()]);
+    }
+
+    #[test]
+    fn kvt_pads_match() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        assert_eq!(check_pads(&netlist, &test_libs()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn finds_missing_footprints() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let mut libs = test_libs();
+        libs.add("Package_TO_SOT_SMD", "/nonexistent");
+
+        let issues = check_pads(&netlist, &libs).unwrap();
+        assert_eq!(
+            issues,
+            vec![
+                PadIssue::FootprintNotFound {
+                    ref_des: "U1".into(),
+                    footprint: "Package_TO_SOT_SMD:SOT-23-5_HandSoldering".into()
+                },
+                PadIssue::FootprintNotFound {
+                    ref_des: "U2".into(),
+                    footprint: "Package_TO_SOT_SMD:SOT-23-5_HandSoldering".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_footprints_are_reported() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        netlist.components[1].footprint = Some("Broken:Truncated".into());
+        let mut libs = test_libs();
+        libs.add(
+            "Broken",
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/resources/test/footprints/Broken.pretty"
+            ),
+        );
+
+        let issues = check_pads(&netlist, &libs).unwrap();
+        assert!(matches!(
+            issues.as_slice(),
+            [PadIssue::InvalidFootprint { ref_des, .. }] if *ref_des == "R1".into()
+        ));
+        assert!(issues[0]
+            .to_string()
+            .starts_with("Footprint Broken:Truncated of R1 is invalid: "));
+    }
+
+    #[test]
+    fn finds_pad_mismatches() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let footprint = FootprintDef::parse(
+            r#"(footprint "SOT-23"
+                (pad "1" smd rect (at -1 -1) (size 1 0.6) (layers "F.Cu"))
+                (pad "2" smd rect (at -1 1) (size 1 0.6) (layers "F.Cu"))
+                (pad "3" smd rect (at 1 0) (size 1 0.6) (layers "F.Cu"))
+                (pad "6" smd rect (at 1 1) (size 1 0.6) (layers "F.Cu")))"#,
+        )
+        .unwrap();
+        let u1 = netlist.find_component("U1".into()).unwrap();
+
+        let issues = compare_pads(u1, &footprint);
+        assert_eq!(
+            issues,
+            vec![
+                PadIssue::PinWithoutPad {
+                    ref_des: "U1".into(),
+                    num: "4".into()
+                },
+                PadIssue::PinWithoutPad {
+                    ref_des: "U1".into(),
+                    num: "5".into()
+                },
+                PadIssue::PadWithoutPin {
+                    ref_des: "U1".into(),
+                    num: "6".to_owned()
+                },
+                PadIssue::PinCountMismatch {
+                    ref_des: "U1".into(),
+                    pins: 5,
+                    pads: 4
+                },
+            ]
+        );
+        assert_eq!(
+            issues[3].to_string(),
+            "U1 has 5 pins but its footprint has 4 pads"
+        );
+    }
+}
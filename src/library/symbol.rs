@@ -0,0 +1,686 @@
+use std::cell::OnceCell;
+use std::collections::{hash_map::Entry, HashMap};
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use crate::sexpr::SExpr;
+use crate::{LibraryError, NetList, ParseError, Part, PartId, PinName, PinNum, PinType, Property};
+
+/// An alternate function of a pin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlternateFunction<'a> {
+    pub name: PinName<'a>,
+    pub typ: PinType,
+}
+
+/// A pin of a library symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolPin<'a> {
+    pub num: PinNum<'a>,
+    pub name: PinName<'a>,
+    pub typ: PinType,
+    /// The unit the pin belongs to, where 0 means that the pin is shared by all units
+    pub unit: u32,
+    pub hidden: bool,
+    pub alternates: Vec<AlternateFunction<'a>>,
+}
+
+/// A symbol read from a `.kicad_sym` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDef<'a> {
+    pub name: &'a str,
+    /// The symbol this symbol is derived from
+    pub extends: Option<&'a str>,
+    pub properties: Vec<Property<'a>>,
+    pub units: u32,
+    pub pins: Vec<SymbolPin<'a>>,
+}
+
+impl<'a> SymbolDef<'a> {
+    pub fn find_property(&self, name: &str) -> Option<&'a str> {
+        self.properties
+            .iter()
+            .find(|prop| prop.name == name)
+            .map(|prop| prop.value)
+    }
+
+    /// Find a pin by number
+    ///
+    /// If the number is used in several units the pin of the first unit is returned.
+    pub fn find_pin(&self, num: PinNum<'_>) -> Option<&SymbolPin<'a>> {
+        self.pins
+            .iter()
+            .find(|pin| pin.num.as_str() == num.as_str())
+    }
+}
+
+impl<'a> TryFrom<&SExpr<'a>> for SymbolDef<'a> {
+    type Error = ParseError;
+
+    fn try_from(value: &SExpr<'a>) -> Result<Self, Self::Error> {
+        let name = value.string().ok_or(ParseError::MissingValue())?;
+        let extends = value.value("extends").ok();
+
+        let properties = value
+            .children("property")
+            .map(|prop| {
+                let mut strings = prop.strings();
+                let name = strings.next().ok_or(ParseError::MissingValue())?;
+                let value = strings.next().unwrap_or_default();
+                Ok(Property { name, value })
+            })
+            .collect::<Result<_, ParseError>>()?;
+
+        let mut units = 0;
+        let mut pins: Vec<SymbolPin<'a>> = vec![];
+        for sub in value.children("symbol") {
+            // Sub symbols are named `<name>_<unit>_<style>`
+            let sub_name = sub.string().unwrap_or_default();
+            let unit = sub_name
+                .rsplit('_')
+                .nth(1)
+                .and_then(|unit| unit.parse().ok())
+                .unwrap_or(0);
+            units = units.max(unit);
+
+            for pin in sub.children("pin") {
+                let pin = parse_pin(pin, unit)?;
+                if !pins.iter().any(|p| p.num == pin.num && p.unit == pin.unit) {
+                    pins.push(pin);
+                }
+            }
+        }
+
+        Ok(SymbolDef {
+            name,
+            extends,
+            properties,
+            units: units.max(1),
+            pins,
+        })
+    }
+}
+
+fn parse_pin<'a>(pin: &SExpr<'a>, unit: u32) -> Result<SymbolPin<'a>, ParseError> {
+    let typ = pin.string().ok_or(ParseError::MissingValue())?;
+    // KiCad 7 marks hidden pins with a bare `hide`, later versions use `(hide yes)`
+    let hidden =
+        pin.strings().any(|s| s == "hide") || pin.value("hide").is_ok_and(|hide| hide == "yes");
+    let alternates = pin
+        .children("alternate")
+        .map(|alt| {
+            let mut strings = alt.strings();
+            let name = strings.next().ok_or(ParseError::MissingValue())?;
+            let typ = strings.next().ok_or(ParseError::MissingValue())?;
+            Ok(AlternateFunction {
+                name: name.into(),
+                typ: typ.try_into()?,
+            })
+        })
+        .collect::<Result<_, ParseError>>()?;
+
+    Ok(SymbolPin {
+        num: pin.value("number")?.into(),
+        name: pin.value("name")?.into(),
+        typ: typ.try_into()?,
+        unit,
+        hidden,
+        alternates,
+    })
+}
+
+/// The symbols of a `.kicad_sym` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolLib<'a> {
+    pub symbols: Vec<SymbolDef<'a>>,
+}
+
+impl<'a> SymbolLib<'a> {
+    /// Parse a symbol library
+    ///
+    /// Derived symbols inherit the pins, units and properties of every symbol up the chain they extend, except for
+    /// pins and properties they set themselves. A pin is overridden by a pin with the same number in the same unit.
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let sexpr = SExpr::try_from(input)?;
+        let label = sexpr.label().unwrap_or_default();
+        if label != "kicad_symbol_lib" {
+            return Err(ParseError::UnexpectedRootLabel(label.to_owned()));
+        }
+
+        let mut symbols: Vec<SymbolDef<'a>> = sexpr
+            .children("symbol")
+            .map(|symbol| symbol.try_into())
+            .collect::<Result<_, _>>()?;
+
+        for i in 0..symbols.len() {
+            let mut parent_name = symbols[i].extends;
+            // Follow the chain of parents, but give up on cycles
+            for _ in 0..symbols.len() {
+                let Some(parent) = parent_name
+                    .and_then(|name| symbols.iter().find(|symbol| symbol.name == name).cloned())
+                else {
+                    break;
+                };
+                let symbol = &mut symbols[i];
+                for pin in parent.pins {
+                    if !symbol
+                        .pins
+                        .iter()
+                        .any(|p| p.num == pin.num && p.unit == pin.unit)
+                    {
+                        symbol.pins.push(pin);
+                    }
+                }
+                symbol.units = symbol.units.max(parent.units);
+                for prop in parent.properties {
+                    if symbol.find_property(prop.name).is_none() {
+                        symbol.properties.push(prop);
+                    }
+                }
+                parent_name = parent.extends;
+            }
+        }
+
+        Ok(SymbolLib { symbols })
+    }
+
+    pub fn find(&self, name: &str) -> Option<&SymbolDef<'a>> {
+        self.symbols.iter().find(|symbol| symbol.name == name)
+    }
+}
+
+/// A map from library nicknames to `.kicad_sym` files
+///
+/// Each file is read the first time it is needed and kept for later lookups.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolLibraries {
+    libs: HashMap<String, (PathBuf, OnceCell<Option<String>>)>,
+}
+
+impl SymbolLibraries {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the `.kicad_sym` file of a library
+    pub fn add(&mut self, nickname: impl Into<String>, path: impl Into<PathBuf>) {
+        self.libs
+            .insert(nickname.into(), (path.into(), OnceCell::new()));
+    }
+
+    pub fn path(&self, lib: &str) -> Option<&PathBuf> {
+        self.libs.get(lib).map(|(path, _)| path)
+    }
+
+    /// Read the `.kicad_sym` file of a library, returning `None` if it cannot be found
+    pub fn read(&self, lib: &str) -> Result<Option<String>, LibraryError> {
+        Ok(self.text(lib)?.map(str::to_owned))
+    }
+
+    /// The contents of the `.kicad_sym` file of a library, which is only read once
+    fn text(&self, lib: &str) -> Result<Option<&str>, LibraryError> {
+        let Some((path, text)) = self.libs.get(lib) else {
+            return Ok(None);
+        };
+        if text.get().is_none() {
+            let _ = text.set(super::read(path)?);
+        }
+        Ok(text.get().and_then(|text| text.as_deref()))
+    }
+
+    /// The symbol of a part, or `None` if the library or the symbol cannot be found
+    pub fn find(&self, part_id: PartId<'_>) -> Result<Option<SymbolDef<'_>>, LibraryError> {
+        let Some(text) = self.text(part_id.lib)? else {
+            return Ok(None);
+        };
+        let symbols = SymbolLib::parse(text).map_err(|source| LibraryError::Parse {
+            path: self.path(part_id.lib).cloned().unwrap_or_default(),
+            source,
+        })?;
+        Ok(symbols.find(part_id.part).cloned())
+    }
+}
+
+/// A difference between a part in the netlist and its symbol in the library
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartIssue<'a> {
+    /// The symbol of the part was not found in the libraries
+    SymbolNotFound(PartId<'a>),
+    /// The library file of the part could not be parsed
+    InvalidLibrary { part_id: PartId<'a>, error: String },
+    /// A pin of the symbol is missing from the part
+    MissingPin { part_id: PartId<'a>, num: String },
+    /// A pin of the part does not exist in the symbol
+    ExtraPin {
+        part_id: PartId<'a>,
+        num: PinNum<'a>,
+    },
+    /// A pin has a different name in the symbol
+    PinNameChanged {
+        part_id: PartId<'a>,
+        num: PinNum<'a>,
+        netlist: PinName<'a>,
+        library: String,
+    },
+    /// A pin has a different electrical type in the symbol
+    PinTypeChanged {
+        part_id: PartId<'a>,
+        num: PinNum<'a>,
+        netlist: PinType,
+        library: PinType,
+    },
+}
+
+impl Display for PartIssue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PartIssue::SymbolNotFound(part_id) => {
+                write!(f, "Symbol {}:{} not found", part_id.lib, part_id.part)
+            }
+            PartIssue::InvalidLibrary { part_id, error } => write!(
+                f,
+                "Library {} of {}:{} is invalid: {error}",
+                part_id.lib, part_id.lib, part_id.part
+            ),
+            PartIssue::MissingPin { part_id, num } => write!(
+                f,
+                "Pin {num} of {}:{} is missing from the netlist",
+                part_id.lib, part_id.part
+            ),
+            PartIssue::ExtraPin { part_id, num } => write!(
+                f,
+                "Pin {num} of {}:{} is not in the library",
+                part_id.lib, part_id.part
+            ),
+            PartIssue::PinNameChanged {
+                part_id,
+                num,
+                netlist,
+                library,
+            } => write!(
+                f,
+                "Pin {num} of {}:{} is named \"{netlist}\" in the netlist but \"{library}\" in the library",
+                part_id.lib, part_id.part
+            ),
+            PartIssue::PinTypeChanged {
+                part_id,
+                num,
+                netlist,
+                library,
+            } => write!(
+                f,
+                "Pin {num} of {}:{} has type {netlist} in the netlist but {library} in the library",
+                part_id.lib, part_id.part
+            ),
+        }
+    }
+}
+
+/// Compare a part of the netlist with its library symbol
+///
+/// A pin whose name and type match one of the alternate functions of the symbol pin agrees with the symbol.
+pub fn compare_part<'a>(part: &Part<'a>, symbol: &SymbolDef<'_>) -> Vec<PartIssue<'a>> {
+    let part_id = part.part_id;
+    let mut issues = vec![];
+
+    for pin in part.pins.iter() {
+        let Some(lib_pin) = symbol.find_pin(pin.num) else {
+            issues.push(PartIssue::ExtraPin {
+                part_id,
+                num: pin.num,
+            });
+            continue;
+        };
+        let is_alternate = lib_pin.alternates.iter().any(|alt| {
+            pin_name(pin.name.as_str()) == pin_name(alt.name.as_str()) && pin.typ == alt.typ
+        });
+        if is_alternate {
+            continue;
+        }
+        if pin_name(pin.name.as_str()) != pin_name(lib_pin.name.as_str()) {
+            issues.push(PartIssue::PinNameChanged {
+                part_id,
+                num: pin.num,
+                netlist: pin.name,
+                library: lib_pin.name.to_string(),
+            });
+        }
+        if pin.typ != lib_pin.typ {
+            issues.push(PartIssue::PinTypeChanged {
+                part_id,
+                num: pin.num,
+                netlist: pin.typ,
+                library: lib_pin.typ,
+            });
+        }
+    }
+
+    let mut seen: Vec<&str> = vec![];
+    for lib_pin in symbol.pins.iter() {
+        let num = lib_pin.num.as_str();
+        if seen.contains(&num) {
+            continue;
+        }
+        seen.push(num);
+        if !part.pins.iter().any(|pin| pin.num.as_str() == num) {
+            issues.push(PartIssue::MissingPin {
+                part_id,
+                num: num.to_owned(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Symbols use `~` for pins without a name, while the netlist leaves the name empty
+fn pin_name(name: &str) -> &str {
+    if name == "~" {
+        ""
+    } else {
+        name
+    }
+}
+
+/// Compare every part of the netlist with its library symbol
+///
+/// Each library file is only read once, and a file which cannot be parsed is reported for each part using it.
+pub fn check_parts<'a>(
+    netlist: &NetList<'a>,
+    libs: &SymbolLibraries,
+) -> Result<Vec<PartIssue<'a>>, LibraryError> {
+    let mut parsed = HashMap::new();
+    for part in netlist.parts.iter() {
+        if let Entry::Vacant(entry) = parsed.entry(part.part_id.lib) {
+            entry.insert(libs.text(part.part_id.lib)?.map(SymbolLib::parse));
+        }
+    }
+
+    let mut issues = vec![];
+    for part in netlist.parts.iter() {
+        let part_id = part.part_id;
+        match parsed.get(part_id.lib) {
+            Some(Some(Ok(symbols))) => match symbols.find(part_id.part) {
+                Some(symbol) => issues.extend(compare_part(part, symbol)),
+                None => issues.push(PartIssue::SymbolNotFound(part_id)),
+            },
+            Some(Some(Err(error))) => issues.push(PartIssue::InvalidLibrary {
+                part_id,
+                error: error.to_string(),
+            }),
+            _ => issues.push(PartIssue::SymbolNotFound(part_id)),
+        }
+    }
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::NetListBuilder;
+
+    fn test_libs() -> SymbolLibraries {
+        let dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/resources/test/symbols"
+        ));
+        let mut libs = SymbolLibraries::new();
+        for lib in ["74xGxx", "Connector", "Device"] {
+            libs.add(lib, dir.join(format!("{lib}.kicad_sym")));
+        }
+        libs
+    }
+
+    const MULTI_UNIT: &str = r#"(kicad_symbol_lib (version 20231120) (generator "kicad_symbol_editor")
+  (symbol "74LS00"
+    (property "Reference" "U" (at 0 1.27 0))
+    (property "Value" "74LS00" (at 0 -1.27 0))
+    (property "ki_description" "quad 2-input NAND gate" (at 0 0 0))
+    (symbol "74LS00_1_1"
+      (pin input line (at -7.62 2.54 0) (length 7.62) (name "~" (effects)) (number "1" (effects)))
+      (pin output line (at 7.62 0 180) (length 7.62) (name "~" (effects)) (number "3" (effects))))
+    (symbol "74LS00_1_2"
+      (pin input inverted (at -7.62 2.54 0) (length 7.62) (name "~" (effects)) (number "1" (effects)))
+      (pin output line (at 7.62 0 180) (length 7.62) (name "~" (effects)) (number "3" (effects))))
+    (symbol "74LS00_2_1"
+      (pin input line (at -7.62 2.54 0) (length 7.62) (name "~" (effects)) (number "4" (effects)))
+      (pin output line (at 7.62 0 180) (length 7.62) (name "~" (effects)) (number "6" (effects))))
+    (symbol "74LS00_3_1"
+      (pin power_in line (at 0 12.7 270) (length 5.08) (hide yes) (name "VCC" (effects)) (number "14" (effects)))
+      (pin bidirectional line (at 0 -12.7 90) (length 5.08)
+        (name "IO" (effects)) (number "7" (effects))
+        (alternate "CLK" input clock)
+        (alternate "OUT" output line))))
+  (symbol "74HC00" (extends "74LS00")
+    (property "Value" "74HC00" (at 0 -1.27 0)))
+  (symbol "74HCT00" (extends "74HC00")
+    (property "Value" "74HCT00" (at 0 -1.27 0))
+    (symbol "74HCT00_3_1"
+      (pin power_in line (at 0 12.7 270) (length 5.08) (name "VDD" (effects)) (number "14" (effects))))))"#;
+
+    #[test]
+    fn can_parse_symbol_library() {
+        let input = test_data!("symbols/74xGxx.kicad_sym");
+        let lib = SymbolLib::parse(&input).unwrap();
+        let symbol = lib.find("74LVC1G00").unwrap();
+
+        assert_eq!(symbol.units, 1);
+        assert_eq!(symbol.pins.len(), 5);
+        assert_eq!(
+            symbol.find_property("ki_description"),
+            Some("Single NAND Gate, Low-Voltage CMOS")
+        );
+        let gnd = symbol.find_pin("3".into()).unwrap();
+        assert_eq!(gnd.name, "GND".into());
+        assert_eq!(gnd.typ, PinType::PowerInput);
+        assert!(gnd.hidden);
+        assert!(!symbol.find_pin("4".into()).unwrap().hidden);
+    }
+
+    #[test]
+    fn can_parse_units_and_alternates() {
+        let lib = SymbolLib::parse(MULTI_UNIT).unwrap();
+        let symbol = lib.find("74LS00").unwrap();
+
+        assert_eq!(symbol.units, 3);
+        let units: Vec<(&str, u32)> = symbol
+            .pins
+            .iter()
+            .map(|pin| (pin.num.as_str(), pin.unit))
+            .collect();
+        assert_eq!(
+            units,
+            vec![("1", 1), ("3", 1), ("4", 2), ("6", 2), ("14", 3), ("7", 3)]
+        );
+        assert!(symbol.find_pin("14".into()).unwrap().hidden);
+        assert_eq!(
+            symbol.find_pin("7".into()).unwrap().alternates,
+            vec![
+                AlternateFunction {
+                    name: "CLK".into(),
+                    typ: PinType::Input
+                },
+                AlternateFunction {
+                    name: "OUT".into(),
+                    typ: PinType::Output
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn derived_symbols_inherit_from_parent() {
+        let lib = SymbolLib::parse(MULTI_UNIT).unwrap();
+        let symbol = lib.find("74HC00").unwrap();
+
+        assert_eq!(symbol.extends, Some("74LS00"));
+        assert_eq!(symbol.units, 3);
+        assert_eq!(symbol.pins.len(), 6);
+        assert_eq!(symbol.find_property("Value"), Some("74HC00"));
+        assert_eq!(
+            symbol.find_property("ki_description"),
+            Some("quad 2-input NAND gate")
+        );
+
+        // Pins are merged along the whole chain
+        let symbol = lib.find("74HCT00").unwrap();
+        assert_eq!(symbol.units, 3);
+        assert_eq!(symbol.pins.len(), 6);
+        assert_eq!(symbol.find_pin("14".into()).unwrap().name, "VDD".into());
+        assert_eq!(symbol.find_pin("7".into()).unwrap().name, "IO".into());
+        assert_eq!(symbol.find_property("Reference"), Some("U"));
+    }
+
+    #[test]
+    fn alternate_functions_match_the_symbol() {
+        let lib = SymbolLib::parse(MULTI_UNIT).unwrap();
+        let part_id = PartId {
+            lib: "74xx",
+            part: "74LS00",
+        };
+        let mut builder = NetListBuilder::new();
+        builder
+            .part(
+                part_id,
+                "quad 2-input NAND gate",
+                &[("7", "CLK", PinType::Input), ("14", "VCC", PinType::Output)],
+            )
+            .component("U1", "74LS00", part_id)
+            .net("1", "/CLK")
+            .net("2", "VCC")
+            .connect("U1", "7", "/CLK")
+            .connect("U1", "14", "VCC");
+        let netlist = builder.build().unwrap();
+
+        let issues = compare_part(&netlist.parts[0], lib.find("74LS00").unwrap());
+        let changed: Vec<&PartIssue> = issues
+            .iter()
+            .filter(|issue| !matches!(issue, PartIssue::MissingPin { .. }))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![&PartIssue::PinTypeChanged {
+                part_id,
+                num: "14".into(),
+                netlist: PinType::Output,
+                library: PinType::PowerInput
+            }]
+        );
+    }
+
+    #[test]
+    fn kvt_matches_library() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        assert_eq!(check_parts(&netlist, &test_libs()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn finds_library_drift() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let lib_input = test_data!("symbols/74xGxx.kicad_sym");
+        let mut lib = SymbolLib::parse(&lib_input).unwrap();
+        let symbol = lib
+            .symbols
+            .iter_mut()
+            .find(|symbol| symbol.name == "74LVC1G00");
+        for pin in symbol.unwrap().pins.iter_mut() {
+            match (pin.num.as_str(), pin.typ) {
+                ("1", _) => pin.name = "A1".into(),
+                ("2", _) => pin.num = "6".into(),
+                (_, PinType::Output) => pin.typ = PinType::TriState,
+                _ => {}
+            }
+        }
+        let part = netlist
+            .find_part(PartId {
+                lib: "74xGxx",
+                part: "74LVC1G00",
+            })
+            .unwrap();
+
+        let part_id = part.part_id;
+        let issues = compare_part(part, lib.find("74LVC1G00").unwrap());
+        assert_eq!(
+            issues,
+            vec![
+                PartIssue::PinNameChanged {
+                    part_id,
+                    num: "1".into(),
+                    netlist: "A".into(),
+                    library: "A1".to_owned()
+                },
+                PartIssue::ExtraPin {
+                    part_id,
+                    num: "2".into()
+                },
+                PartIssue::PinTypeChanged {
+                    part_id,
+                    num: "4".into(),
+                    netlist: PinType::Output,
+                    library: PinType::TriState
+                },
+                PartIssue::MissingPin {
+                    part_id,
+                    num: "6".to_owned()
+                },
+            ]
+        );
+        assert_eq!(
+            issues[2].to_string(),
+            "Pin 4 of 74xGxx:74LVC1G00 has type output in the netlist but tri_state in the library"
+        );
+    }
+
+    #[test]
+    fn symbols_can_be_found_by_part_id() {
+        let libs = test_libs();
+        let part_id = |lib, part| PartId { lib, part };
+
+        let symbol = libs.find(part_id("74xGxx", "74LVC1G00")).unwrap().unwrap();
+        assert_eq!(symbol.name, "74LVC1G00");
+        assert_eq!(symbol.find_pin("4".into()).unwrap().typ, PinType::Output);
+        assert_eq!(libs.find(part_id("74xGxx", "74LVC1G99")).unwrap(), None);
+        assert_eq!(libs.find(part_id("Unknown", "R")).unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_libraries_are_reported() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let mut libs = test_libs();
+        libs.add(
+            "Device",
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/resources/test/symbols/Broken.kicad_sym"
+            ),
+        );
+
+        let issues = check_parts(&netlist, &libs).unwrap();
+        assert!(matches!(
+            issues.as_slice(),
+            [PartIssue::InvalidLibrary { part_id, .. }] if part_id.part == "R"
+        ));
+        assert!(issues[0]
+            .to_string()
+            .starts_with("Library Device of Device:R is invalid: "));
+    }
+
+    #[test]
+    fn unknown_library_is_reported() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let mut libs = test_libs();
+        libs.add("Device", "/nonexistent/Device.kicad_sym");
+
+        assert_eq!(
+            check_parts(&netlist, &libs).unwrap(),
+            vec![PartIssue::SymbolNotFound(PartId {
+                lib: "Device",
+                part: "R"
+            })]
+        );
+    }
+}
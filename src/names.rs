@@ -0,0 +1,263 @@
+//! The structure of reference designators, pin numbers and net names, and their natural ordering
+//!
+//! Ordering is natural, so `R2` sorts before `R10`, and falls back to comparing the plain strings so that it agrees
+//! with equality.
+
+use std::cmp::Ordering;
+
+use crate::natural::natural_cmp;
+use crate::{NetName, PinNum, RefDes};
+
+impl<'a> RefDes<'a> {
+    /// The letters before the number, such as `R` in `R12` or `R?`
+    pub fn prefix(&self) -> &'a str {
+        let end = self
+            .0
+            .find(|c: char| c.is_ascii_digit() || c == '?')
+            .unwrap_or(self.0.len());
+        &self.0[..end]
+    }
+
+    /// The number after the prefix, which is `None` when unannotated or followed by anything else, as in `U1A`
+    pub fn number(&self) -> Option<u64> {
+        let rest = &self.0[self.prefix().len()..];
+        if rest.is_empty() || !rest.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        rest.parse().ok()
+    }
+
+    /// Whether a number has been assigned, rather than `?` as in `R?`
+    pub fn is_annotated(&self) -> bool {
+        !self.0.ends_with('?')
+    }
+}
+
+impl Ord for RefDes<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        natural_cmp(self.0, other.0).then_with(|| self.0.cmp(other.0))
+    }
+}
+
+impl PartialOrd for RefDes<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> PinNum<'a> {
+    /// The pin number, if it is a plain number
+    pub fn number(&self) -> Option<u32> {
+        if self.0.is_empty() || !self.0.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        self.0.parse().ok()
+    }
+
+    /// The row and column of a grid array pin, such as `("AA", 12)` for `AA12`
+    pub fn grid(&self) -> Option<(&'a str, u32)> {
+        let split = self
+            .0
+            .find(|c: char| !c.is_ascii_uppercase())
+            .unwrap_or(self.0.len());
+        let (row, column) = self.0.split_at(split);
+        if row.is_empty() || column.is_empty() || !column.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some((row, column.parse().ok()?))
+    }
+}
+
+/// Numbers sort before grid pins, which sort before anything else, and grid rows sort by length first, so `Y1`
+/// comes before `AA1`
+impl Ord for PinNum<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ord = match (self.number(), other.number()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => match (self.grid(), other.grid()) {
+                (Some((a_row, a_col)), Some((b_row, b_col))) => a_row
+                    .len()
+                    .cmp(&b_row.len())
+                    .then_with(|| a_row.cmp(b_row))
+                    .then_with(|| a_col.cmp(&b_col)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => natural_cmp(self.0, other.0),
+            },
+        };
+        ord.then_with(|| self.0.cmp(other.0))
+    }
+}
+
+impl PartialOrd for PinNum<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> NetName<'a> {
+    /// Where the local name starts, which is after the last `/` outside of an automatic name
+    fn local_start(&self) -> usize {
+        let end = ["Net-(", "unconnected-("]
+            .iter()
+            .filter_map(|marker| self.0.find(marker))
+            .min()
+            .unwrap_or(self.0.len());
+        self.0[..end].rfind('/').map_or(0, |pos| pos + 1)
+    }
+
+    /// The sheet path including the trailing `/`, such as `/power/` in `/power/+5V`, if there is one
+    pub fn path(&self) -> Option<&'a str> {
+        match self.local_start() {
+            0 => None,
+            start => Some(&self.0[..start]),
+        }
+    }
+
+    /// The name without its sheet path
+    pub fn local_name(&self) -> &'a str {
+        &self.0[self.local_start()..]
+    }
+
+    /// The pin an automatic name such as `Net-(U1-Out)` was derived from, as the reference and the pin name or number
+    ///
+    /// KiCad joins the reference and the pin with a `-`, so the split is taken at the first `-` after a digit or `?`,
+    /// where an annotated reference ends, which keeps references like `PS-1` and pin names like `D-` whole. A pin
+    /// without a name is written as `Pad` and its number, which cannot be told apart from a pin actually named
+    /// `Pad5`, so the prefix is removed whenever the rest looks like a pin number.
+    pub fn auto_pin(&self) -> Option<(RefDes<'a>, &'a str)> {
+        let inner = self
+            .local_name()
+            .strip_prefix("Net-(")
+            .or_else(|| self.local_name().strip_prefix("unconnected-("))?
+            .strip_suffix(')')?;
+        let split = inner
+            .match_indices('-')
+            .map(|(pos, _)| pos)
+            .find(|&pos| inner[..pos].ends_with(|c: char| c.is_ascii_digit() || c == '?'))
+            .or_else(|| inner.find('-'))?;
+        let (ref_des, pin) = (&inner[..split], &inner[split + 1..]);
+        let pin = match pin.strip_prefix("Pad") {
+            Some(num) if PinNum(num).number().is_some() || PinNum(num).grid().is_some() => num,
+            _ => pin,
+        };
+        Some((RefDes(ref_des), pin))
+    }
+
+    /// Whether the name was generated by KiCad rather than given by a label
+    pub fn is_auto(&self) -> bool {
+        self.auto_pin().is_some()
+    }
+}
+
+impl Ord for NetName<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        natural_cmp(self.0, other.0).then_with(|| self.0.cmp(other.0))
+    }
+}
+
+impl PartialOrd for NetName<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("R12", "R", Some(12), true)]
+    #[case("R?", "R", None, false)]
+    #[case("SW3", "SW", Some(3), true)]
+    #[case("U1A", "U", None, true)]
+    #[case("TP", "TP", None, true)]
+    fn can_split_ref_des(
+        #[case] ref_des: &str,
+        #[case] prefix: &str,
+        #[case] number: Option<u64>,
+        #[case] annotated: bool,
+    ) {
+        let ref_des = RefDes::from(ref_des);
+        assert_eq!(ref_des.prefix(), prefix);
+        assert_eq!(ref_des.number(), number);
+        assert_eq!(ref_des.is_annotated(), annotated);
+    }
+
+    #[rstest]
+    #[case("12", Some(12), None)]
+    #[case("A1", None, Some(("A", 1)))]
+    #[case("AA12", None, Some(("AA", 12)))]
+    #[case("EP", None, None)]
+    #[case("", None, None)]
+    fn can_split_pin_num(
+        #[case] num: &str,
+        #[case] number: Option<u32>,
+        #[case] grid: Option<(&str, u32)>,
+    ) {
+        let num = PinNum::from(num);
+        assert_eq!(num.number(), number);
+        assert_eq!(num.grid(), grid);
+    }
+
+    #[rstest]
+    #[case("GND", None, "GND", None)]
+    #[case("/SDA", Some("/"), "SDA", None)]
+    #[case("/power/+5V", Some("/power/"), "+5V", None)]
+    #[case("Net-(U1-Out)", None, "Net-(U1-Out)", Some(("U1", "Out")))]
+    #[case("Net-(U1-A/B)", None, "Net-(U1-A/B)", Some(("U1", "A/B")))]
+    #[case("unconnected-(J1-Pad3)", None, "unconnected-(J1-Pad3)", Some(("J1", "3")))]
+    #[case("unconnected-(U1-A/B)", None, "unconnected-(U1-A/B)", Some(("U1", "A/B")))]
+    #[case(
+        "/sub/unconnected-(U1-A/B)",
+        Some("/sub/"),
+        "unconnected-(U1-A/B)",
+        Some(("U1", "A/B"))
+    )]
+    #[case("/sub/Net-(U1-Pad2)", Some("/sub/"), "Net-(U1-Pad2)", Some(("U1", "2")))]
+    #[case("Net-(U2-PadA12)", None, "Net-(U2-PadA12)", Some(("U2", "A12")))]
+    #[case("Net-(U1-Pads)", None, "Net-(U1-Pads)", Some(("U1", "Pads")))]
+    #[case("Net-(PS-1-Pad2)", None, "Net-(PS-1-Pad2)", Some(("PS-1", "2")))]
+    #[case("Net-(J1-D-)", None, "Net-(J1-D-)", Some(("J1", "D-")))]
+    fn can_split_net_name(
+        #[case] name: &str,
+        #[case] path: Option<&str>,
+        #[case] local: &str,
+        #[case] auto: Option<(&str, &str)>,
+    ) {
+        let name = NetName::from(name);
+        assert_eq!(name.path(), path);
+        assert_eq!(name.local_name(), local);
+        assert_eq!(name.auto_pin().map(|(r, p)| (r.as_str(), p)), auto);
+    }
+
+    #[test]
+    fn sorts_naturally() {
+        let mut refs: Vec<RefDes> = ["R10", "R?", "C1", "R2", "r2"]
+            .into_iter()
+            .map(RefDes::from)
+            .collect();
+        refs.sort();
+        let refs: Vec<&str> = refs.iter().map(|r| r.as_str()).collect();
+        assert_eq!(refs, vec!["C1", "R2", "r2", "R10", "R?"]);
+
+        let mut pins: Vec<PinNum> = ["AA1", "B2", "10", "2", "EP", "A10", "Y1", "A2"]
+            .into_iter()
+            .map(PinNum::from)
+            .collect();
+        pins.sort();
+        let pins: Vec<&str> = pins.iter().map(|p| p.as_str()).collect();
+        assert_eq!(pins, vec!["2", "10", "A2", "A10", "B2", "Y1", "AA1", "EP"]);
+
+        let mut nets: Vec<NetName> = ["/OUT10", "/OUT2", "GND"]
+            .into_iter()
+            .map(NetName::from)
+            .collect();
+        nets.sort();
+        let nets: Vec<&str> = nets.iter().map(|n| n.as_str()).collect();
+        assert_eq!(nets, vec!["/OUT2", "/OUT10", "GND"]);
+    }
+}
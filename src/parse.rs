@@ -1,6 +1,6 @@
 use crate::{
-    raw, Component, ComponentPin, Net, NetList, NetNode, ParseError, Part, PartId, PartPin,
-    PinType, Property,
+    raw, Component, ComponentPin, Design, Net, NetList, NetNode, ParseError, Part, PartId, PartPin,
+    PinType, Property, SheetPath,
 };
 
 impl TryFrom<&str> for PinType {
@@ -17,6 +17,7 @@ impl TryFrom<&str> for PinType {
             "tri_state" => Ok(Self::TriState),
             "passive" => Ok(Self::Passive),
             "free" => Ok(Self::Free),
+            "unspecified" => Ok(Self::Unspecified),
             "power_in" => Ok(Self::PowerInput),
             "power_out" => Ok(Self::PowerOutput),
             "open_collector" => Ok(Self::OpenCollector),
@@ -47,6 +48,7 @@ impl<'a> TryFrom<raw::Part<'a>> for Part<'a> {
             part,
             lib,
             description,
+            footprints,
             pins,
         } = value;
 
@@ -60,6 +62,7 @@ impl<'a> TryFrom<raw::Part<'a>> for Part<'a> {
         Ok(Part {
             part_id,
             description,
+            footprint_filters: footprints,
             pins,
             components: vec![],
         })
@@ -76,7 +79,10 @@ impl<'a> TryFrom<raw::Component<'a>> for Component<'a> {
             part,
             lib,
             properties,
+            fields,
             footprint,
+            sheetpath,
+            tstamp,
         } = value;
         let part_id = PartId { lib, part };
 
@@ -85,13 +91,21 @@ impl<'a> TryFrom<raw::Component<'a>> for Component<'a> {
             .map(|(name, value)| Property { name, value })
             .collect();
 
+        let fields = fields
+            .into_iter()
+            .map(|(name, value)| Property { name, value })
+            .collect();
+
         Ok(Component {
             ref_des: ref_des.into(),
             value: value.into(),
             part_id,
             properties,
+            fields,
             footprint: footprint.map(|s| s.into()),
             pins: vec![],
+            sheet_path: sheetpath.map(|(names, tstamps)| SheetPath { names, tstamps }),
+            tstamp,
         })
     }
 }
@@ -132,11 +146,33 @@ impl<'a> TryFrom<raw::Net<'a>> for Net<'a> {
     }
 }
 
+impl<'a> From<raw::Design<'a>> for Design<'a> {
+    fn from(value: raw::Design<'a>) -> Self {
+        let raw::Design {
+            source,
+            date,
+            tool,
+            title,
+            company,
+            rev,
+        } = value;
+        Design {
+            source,
+            date,
+            tool,
+            title,
+            company,
+            rev,
+        }
+    }
+}
+
 impl<'a> TryFrom<raw::NetList<'a>> for NetList<'a> {
     type Error = ParseError;
 
     fn try_from(value: raw::NetList<'a>) -> Result<Self, Self::Error> {
         let raw::NetList {
+            design,
             components,
             parts,
             nets,
@@ -183,10 +219,10 @@ impl<'a> TryFrom<raw::NetList<'a>> for NetList<'a> {
                             comp.ref_des.0.to_string(),
                             num.0.to_string(),
                         ))?;
-                    let net = net.name.clone().into();
+                    let net = net.name;
                     Ok(ComponentPin {
-                        num: num.clone(),
-                        name: name.clone(),
+                        num: *num,
+                        name: *name,
                         typ: *typ,
                         net,
                     })
@@ -199,7 +235,7 @@ impl<'a> TryFrom<raw::NetList<'a>> for NetList<'a> {
                 .iter()
                 .filter_map(|comp| {
                     if comp.part_id == part.part_id {
-                        Some(comp.ref_des.clone())
+                        Some(comp.ref_des)
                     } else {
                         None
                     }
@@ -214,6 +250,7 @@ impl<'a> TryFrom<raw::NetList<'a>> for NetList<'a> {
         }
 
         Ok(NetList {
+            design: design.into(),
             components,
             parts,
             nets,
@@ -0,0 +1,309 @@
+//! Power domains and signals crossing between them
+//!
+//! Each component is supplied by the power rails its power input pins connect to, as classified by
+//! [`classify`](crate::classify). A signal crosses domains when an output of a component on one supply voltage drives
+//! an input of a component on another, unless one of them is a level shifter. Components on several voltages, and
+//! rails without a voltage in their name, are left out of the comparison.
+
+use std::fmt::Display;
+
+use crate::classify::{classify, ClassifyConfig, NetClass};
+use crate::graph::PinRef;
+use crate::{NetList, NetName, PinType, RefDes};
+
+/// Settings for the power analysis
+#[derive(Debug, Clone)]
+pub struct PowerConfig {
+    pub classify: ClassifyConfig,
+    /// Part names or values of level shifters, which may connect different voltages
+    pub level_shifters: Vec<String>,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        let level_shifters = [
+            "74LVC1T45",
+            "74LVC2T45",
+            "74LVC8T245",
+            "74AVC4T245",
+            "TXB0102",
+            "TXB0104",
+            "TXB0108",
+            "TXS0102",
+            "TXS0104E",
+            "TXS0108E",
+            "PCA9306",
+        ];
+        Self {
+            classify: ClassifyConfig::default(),
+            level_shifters: level_shifters.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// The rails a component is supplied from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Supply<'a> {
+    pub ref_des: RefDes<'a>,
+    pub rails: Vec<NetName<'a>>,
+    /// The supply voltage, if all rails with a known voltage agree
+    pub voltage: Option<f64>,
+}
+
+/// The components supplied from a rail
+#[derive(Debug, Clone, PartialEq)]
+pub struct Domain<'a> {
+    pub rail: NetName<'a>,
+    pub voltage: Option<f64>,
+    pub components: Vec<RefDes<'a>>,
+}
+
+/// An output driving an input on a different supply voltage
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossing<'a> {
+    pub net: NetName<'a>,
+    pub driver: PinRef<'a>,
+    pub driver_voltage: f64,
+    pub receiver: PinRef<'a>,
+    pub receiver_voltage: f64,
+}
+
+impl Display for Crossing<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}.{} ({}V) drives {}.{} ({}V)",
+            self.net,
+            self.driver.ref_des,
+            self.driver.num,
+            self.driver_voltage,
+            self.receiver.ref_des,
+            self.receiver.num,
+            self.receiver_voltage
+        )
+    }
+}
+
+/// The result of the power analysis
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PowerReport<'a> {
+    /// The components with at least one power input pin on a rail
+    pub supplies: Vec<Supply<'a>>,
+    pub domains: Vec<Domain<'a>>,
+    pub crossings: Vec<Crossing<'a>>,
+}
+
+impl<'a> PowerReport<'a> {
+    pub fn find_supply(&self, ref_des: RefDes<'_>) -> Option<&Supply<'a>> {
+        self.supplies
+            .iter()
+            .find(|supply| supply.ref_des.as_str() == ref_des.as_str())
+    }
+}
+
+impl Display for PowerReport<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for domain in self.domains.iter() {
+            match domain.voltage {
+                Some(voltage) => writeln!(f, "{} ({voltage}V):", domain.rail)?,
+                None => writeln!(f, "{}:", domain.rail)?,
+            }
+            let components: Vec<String> = domain.components.iter().map(|c| c.to_string()).collect();
+            writeln!(f, "  {}", components.join(", "))?;
+        }
+        for crossing in self.crossings.iter() {
+            writeln!(f, "{crossing}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the supplies of every component and the signals crossing between voltages
+pub fn analyze<'a>(netlist: &NetList<'a>, config: &PowerConfig) -> PowerReport<'a> {
+    let classes = classify(netlist, &config.classify);
+
+    let mut domains: Vec<Domain<'a>> = vec![];
+    let mut supplies: Vec<Supply<'a>> = vec![];
+    for (net, info) in netlist.nets.iter().zip(classes.iter()) {
+        if info.class != NetClass::Power {
+            continue;
+        }
+        let mut domain = Domain {
+            rail: net.name,
+            voltage: info.voltage,
+            components: vec![],
+        };
+        for node in net.nodes.iter() {
+            if node.typ != PinType::PowerInput || domain.components.contains(&node.ref_des) {
+                continue;
+            }
+            domain.components.push(node.ref_des);
+            match supplies.iter_mut().find(|s| s.ref_des == node.ref_des) {
+                Some(supply) => supply.rails.push(net.name),
+                None => supplies.push(Supply {
+                    ref_des: node.ref_des,
+                    rails: vec![net.name],
+                    voltage: None,
+                }),
+            }
+        }
+        if !domain.components.is_empty() {
+            domains.push(domain);
+        }
+    }
+
+    for supply in supplies.iter_mut() {
+        let mut voltages = domains
+            .iter()
+            .filter(|domain| supply.rails.contains(&domain.rail))
+            .filter_map(|domain| domain.voltage);
+        let first = voltages.next();
+        if voltages.all(|v| Some(v) == first) {
+            supply.voltage = first;
+        }
+    }
+
+    let is_level_shifter = |ref_des: RefDes<'_>| {
+        netlist.find_component(ref_des).is_some_and(|comp| {
+            config
+                .level_shifters
+                .iter()
+                .any(|name| name == comp.part_id.part || name == comp.value.as_str())
+        })
+    };
+    let voltage = |ref_des: RefDes<'_>| {
+        if is_level_shifter(ref_des) {
+            return None;
+        }
+        supplies
+            .iter()
+            .find(|supply| supply.ref_des == ref_des)
+            .and_then(|supply| supply.voltage)
+    };
+
+    let mut crossings = vec![];
+    for (net, info) in netlist.nets.iter().zip(classes.iter()) {
+        if info.class != NetClass::Signal {
+            continue;
+        }
+        for driver in net.nodes.iter().filter(|node| node.typ.drives()) {
+            let Some(driver_voltage) = voltage(driver.ref_des) else {
+                continue;
+            };
+            for receiver in net.nodes.iter().filter(|node| node.typ.receives()) {
+                if receiver.ref_des == driver.ref_des {
+                    continue;
+                }
+                let Some(receiver_voltage) = voltage(receiver.ref_des) else {
+                    continue;
+                };
+                if driver_voltage != receiver_voltage {
+                    crossings.push(Crossing {
+                        net: net.name,
+                        driver: PinRef {
+                            ref_des: driver.ref_des,
+                            num: driver.num,
+                        },
+                        driver_voltage,
+                        receiver: PinRef {
+                            ref_des: receiver.ref_des,
+                            num: receiver.num,
+                        },
+                        receiver_voltage,
+                    });
+                }
+            }
+        }
+    }
+
+    PowerReport {
+        supplies,
+        domains,
+        crossings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Net, NetList};
+
+    /// Put U1 on +3V3 and U2 on +5V
+    fn split_supplies(netlist: &mut NetList<'_>) {
+        let vcc = netlist.find_net("VCC".into()).unwrap().clone();
+        let (u2, rest): (Vec<_>, Vec<_>) = vcc
+            .nodes
+            .into_iter()
+            .partition(|node| node.ref_des == "U2".into());
+        netlist.nets.retain(|net| net.name != "VCC".into());
+        netlist.nets.push(Net {
+            code: "7".into(),
+            name: "+3V3".into(),
+            nodes: rest,
+        });
+        netlist.nets.push(Net {
+            code: "8".into(),
+            name: "+5V".into(),
+            nodes: u2,
+        });
+    }
+
+    #[test]
+    fn kvt_has_one_domain() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let report = analyze(&netlist, &PowerConfig::default());
+
+        assert_eq!(
+            report.domains,
+            vec![Domain {
+                rail: "VCC".into(),
+                voltage: None,
+                components: vec!["U1".into(), "U2".into()],
+            }]
+        );
+        assert_eq!(
+            report.find_supply("U2".into()).unwrap().rails,
+            vec!["VCC".into()]
+        );
+        assert_eq!(report.crossings, vec![]);
+        assert_eq!(report.to_string(), "VCC:\n  U1, U2\n");
+    }
+
+    #[test]
+    fn finds_crossings() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        split_supplies(&mut netlist);
+        let report = analyze(&netlist, &PowerConfig::default());
+
+        assert_eq!(report.find_supply("U1".into()).unwrap().voltage, Some(3.3));
+        assert_eq!(report.find_supply("U2".into()).unwrap().voltage, Some(5.0));
+        assert_eq!(
+            report.crossings,
+            vec![Crossing {
+                net: "Net-(U1-Out)".into(),
+                driver: PinRef {
+                    ref_des: "U1".into(),
+                    num: "4".into()
+                },
+                driver_voltage: 3.3,
+                receiver: PinRef {
+                    ref_des: "U2".into(),
+                    num: "1".into()
+                },
+                receiver_voltage: 5.0,
+            }]
+        );
+        assert_eq!(
+            report.crossings[0].to_string(),
+            "Net-(U1-Out): U1.4 (3.3V) drives U2.1 (5V)"
+        );
+
+        let config = PowerConfig {
+            level_shifters: vec!["74LVC1G00".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(analyze(&netlist, &config).crossings, vec![]);
+    }
+}
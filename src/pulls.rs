@@ -0,0 +1,364 @@
+//! Pull-up and pull-down resistors and series termination
+//!
+//! A resistor between a signal and a power rail pulls the signal up, and one between a signal and ground pulls it
+//! down, with nets classified by [`classify`](crate::classify). A small resistor between a net driven by an output
+//! and a net with an input is taken to be a series termination.
+//!
+//! Open collector pins need a pull-up on their net and open emitter pins a pull-down. A signal pulled both ways is
+//! reported as well, since a divider on a signal is rarely intended.
+
+use std::fmt::Display;
+
+use crate::classify::{classify, ClassifyConfig, NetClass};
+use crate::graph::PinRef;
+use crate::quantity::{Quantity, Unit};
+use crate::{Component, NetList, NetName, NetNode, PinType, RefDes};
+
+/// Settings for the pull analysis
+#[derive(Debug, Clone)]
+pub struct PullConfig {
+    pub classify: ClassifyConfig,
+    /// The reference designator prefix of resistors
+    pub resistor_prefix: String,
+    /// The largest resistance in ohms which counts as a series termination
+    pub max_termination: f64,
+}
+
+impl Default for PullConfig {
+    fn default() -> Self {
+        Self {
+            classify: ClassifyConfig::default(),
+            resistor_prefix: "R".to_owned(),
+            max_termination: 100.0,
+        }
+    }
+}
+
+/// A resistor from a signal to a rail
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pull<'a> {
+    pub resistor: RefDes<'a>,
+    pub rail: NetName<'a>,
+    /// The resistance in ohms, if the value could be parsed
+    pub resistance: Option<f64>,
+}
+
+/// The pull resistors of a signal
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalPulls<'a> {
+    pub net: NetName<'a>,
+    pub pull_ups: Vec<Pull<'a>>,
+    pub pull_downs: Vec<Pull<'a>>,
+}
+
+/// A series resistor between an output and an input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Termination<'a> {
+    pub resistor: RefDes<'a>,
+    pub resistance: f64,
+    pub driver: PinRef<'a>,
+    pub receivers: Vec<PinRef<'a>>,
+}
+
+impl Display for Termination<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let receivers: Vec<String> = self
+            .receivers
+            .iter()
+            .map(|pin| format!("{}.{}", pin.ref_des, pin.num))
+            .collect();
+        write!(
+            f,
+            "{} ({}Ω) terminates {}.{} to {}",
+            self.resistor,
+            self.resistance,
+            self.driver.ref_des,
+            self.driver.num,
+            receivers.join(", ")
+        )
+    }
+}
+
+/// A problem found by the analysis
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PullIssue<'a> {
+    /// An open collector pin without a pull-up on its net
+    MissingPullUp { net: NetName<'a>, pin: PinRef<'a> },
+    /// An open emitter pin without a pull-down on its net
+    MissingPullDown { net: NetName<'a>, pin: PinRef<'a> },
+    /// A signal with both a pull-up and a pull-down
+    ConflictingPulls {
+        net: NetName<'a>,
+        pull_up: RefDes<'a>,
+        pull_down: RefDes<'a>,
+    },
+}
+
+impl Display for PullIssue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PullIssue::MissingPullUp { net, pin } => {
+                write!(
+                    f,
+                    "{net} has no pull-up for open collector {}.{}",
+                    pin.ref_des, pin.num
+                )
+            }
+            PullIssue::MissingPullDown { net, pin } => {
+                write!(
+                    f,
+                    "{net} has no pull-down for open emitter {}.{}",
+                    pin.ref_des, pin.num
+                )
+            }
+            PullIssue::ConflictingPulls {
+                net,
+                pull_up,
+                pull_down,
+            } => write!(f, "{net} is pulled up by {pull_up} and down by {pull_down}"),
+        }
+    }
+}
+
+/// The result of the pull analysis
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PullReport<'a> {
+    /// The signals with at least one pull resistor
+    pub signals: Vec<SignalPulls<'a>>,
+    pub terminations: Vec<Termination<'a>>,
+    pub issues: Vec<PullIssue<'a>>,
+}
+
+impl<'a> PullReport<'a> {
+    pub fn find_signal(&self, net: NetName<'_>) -> Option<&SignalPulls<'a>> {
+        self.signals
+            .iter()
+            .find(|signal| signal.net.as_str() == net.as_str())
+    }
+}
+
+fn is_resistor(comp: &Component<'_>, prefix: &str) -> bool {
+    comp.pins.len() == 2 && comp.pins[0].net != comp.pins[1].net && comp.ref_des.prefix() == prefix
+}
+
+/// Find the pull resistors of every signal, series terminations and missing or conflicting pulls
+pub fn analyze<'a>(netlist: &NetList<'a>, config: &PullConfig) -> PullReport<'a> {
+    let classes = classify(netlist, &config.classify);
+    let class = |net: NetName<'_>| {
+        classes
+            .iter()
+            .find(|info| info.net.as_str() == net.as_str())
+            .map(|info| info.class)
+    };
+
+    let mut signals: Vec<SignalPulls<'a>> = netlist
+        .nets
+        .iter()
+        .filter(|net| class(net.name) == Some(NetClass::Signal))
+        .map(|net| SignalPulls {
+            net: net.name,
+            pull_ups: vec![],
+            pull_downs: vec![],
+        })
+        .collect();
+
+    let mut terminations = vec![];
+    let resistors = netlist
+        .components
+        .iter()
+        .filter(|comp| is_resistor(comp, &config.resistor_prefix));
+    for comp in resistors {
+        let (a, b) = (comp.pins[0].net, comp.pins[1].net);
+        let resistance = Quantity::of(comp)
+            .filter(|q| q.is(Unit::Ohm))
+            .map(|q| q.value);
+        match (class(a), class(b)) {
+            (Some(NetClass::Signal), Some(NetClass::Signal)) => {
+                let Some(resistance) = resistance.filter(|r| *r <= config.max_termination) else {
+                    continue;
+                };
+                if let Some(termination) = termination(netlist, comp.ref_des, resistance, a, b)
+                    .or_else(|| termination(netlist, comp.ref_des, resistance, b, a))
+                {
+                    terminations.push(termination);
+                }
+            }
+            (Some(NetClass::Signal), Some(rail_class))
+            | (Some(rail_class), Some(NetClass::Signal)) => {
+                let (signal, rail) = if class(a) == Some(NetClass::Signal) {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                let Some(signal) = signals.iter_mut().find(|s| s.net == signal) else {
+                    continue;
+                };
+                let pull = Pull {
+                    resistor: comp.ref_des,
+                    rail,
+                    resistance,
+                };
+                match rail_class {
+                    NetClass::Power => signal.pull_ups.push(pull),
+                    _ => signal.pull_downs.push(pull),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut issues = vec![];
+    for net in netlist.nets.iter() {
+        let Some(signal) = signals.iter().find(|s| s.net == net.name) else {
+            continue;
+        };
+        for node in net.nodes.iter() {
+            let pin = PinRef {
+                ref_des: node.ref_des,
+                num: node.num,
+            };
+            match node.typ {
+                PinType::OpenCollector if signal.pull_ups.is_empty() => {
+                    issues.push(PullIssue::MissingPullUp { net: net.name, pin })
+                }
+                PinType::OpenEmitter if signal.pull_downs.is_empty() => {
+                    issues.push(PullIssue::MissingPullDown { net: net.name, pin })
+                }
+                _ => {}
+            }
+        }
+        if let (Some(up), Some(down)) = (signal.pull_ups.first(), signal.pull_downs.first()) {
+            issues.push(PullIssue::ConflictingPulls {
+                net: net.name,
+                pull_up: up.resistor,
+                pull_down: down.resistor,
+            });
+        }
+    }
+
+    signals.retain(|s| !s.pull_ups.is_empty() || !s.pull_downs.is_empty());
+    PullReport {
+        signals,
+        terminations,
+        issues,
+    }
+}
+
+/// A termination from an output on `from` to the inputs on `to`
+fn termination<'a>(
+    netlist: &NetList<'a>,
+    resistor: RefDes<'a>,
+    resistance: f64,
+    from: NetName<'a>,
+    to: NetName<'a>,
+) -> Option<Termination<'a>> {
+    let pin = |node: &NetNode<'a>| PinRef {
+        ref_des: node.ref_des,
+        num: node.num,
+    };
+    let driver = netlist
+        .find_net(from)?
+        .nodes
+        .iter()
+        .find(|node| node.ref_des != resistor && node.typ.drives())
+        .map(pin)?;
+    let receivers: Vec<PinRef<'a>> = netlist
+        .find_net(to)?
+        .nodes
+        .iter()
+        .filter(|node| node.ref_des != resistor && node.typ.receives())
+        .map(pin)
+        .collect();
+    (!receivers.is_empty()).then_some(Termination {
+        resistor,
+        resistance,
+        driver,
+        receivers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_pulls() {
+        let input = test_data!("analysis.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let report = analyze(&netlist, &PullConfig::default());
+
+        let sda = report.find_signal("/SDA".into()).unwrap();
+        assert_eq!(
+            sda.pull_ups,
+            vec![Pull {
+                resistor: "R1".into(),
+                rail: "+3V3".into(),
+                resistance: Some(4700.0),
+            }]
+        );
+        assert_eq!(sda.pull_downs, vec![]);
+        let en = report.find_signal("/EN".into()).unwrap();
+        assert_eq!(en.pull_downs[0].rail, "GND".into());
+        assert_eq!(en.pull_ups, vec![]);
+        assert!(report.find_signal("/INT".into()).is_none());
+
+        assert_eq!(
+            report.issues,
+            vec![
+                PullIssue::MissingPullUp {
+                    net: "/INT".into(),
+                    pin: PinRef {
+                        ref_des: "U1".into(),
+                        num: "5".into()
+                    }
+                },
+                PullIssue::ConflictingPulls {
+                    net: "/Y".into(),
+                    pull_up: "R4".into(),
+                    pull_down: "R5".into()
+                },
+            ]
+        );
+        assert_eq!(
+            report.issues[0].to_string(),
+            "/INT has no pull-up for open collector U1.5"
+        );
+        assert_eq!(
+            report.issues[1].to_string(),
+            "/Y is pulled up by R4 and down by R5"
+        );
+    }
+
+    #[test]
+    fn finds_terminations() {
+        let input = test_data!("analysis.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let report = analyze(&netlist, &PullConfig::default());
+
+        assert_eq!(
+            report.terminations,
+            vec![Termination {
+                resistor: "R3".into(),
+                resistance: 33.0,
+                driver: PinRef {
+                    ref_des: "U1".into(),
+                    num: "3".into()
+                },
+                receivers: vec![PinRef {
+                    ref_des: "U2".into(),
+                    num: "2".into()
+                }],
+            }]
+        );
+        assert_eq!(
+            report.terminations[0].to_string(),
+            "R3 (33Ω) terminates U1.3 to U2.2"
+        );
+
+        let config = PullConfig {
+            max_termination: 22.0,
+            ..Default::default()
+        };
+        assert_eq!(analyze(&netlist, &config).terminations, vec![]);
+    }
+}
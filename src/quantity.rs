@@ -0,0 +1,301 @@
+//! Numeric component values
+//!
+//! Values are written in engineering notation such as `100n`, `2.2uF`, `4k7` or `10R`, where a prefix may stand in
+//! for the decimal point. Further words, such as a voltage rating in `10u/25V` or a tolerance in `10k 1%`, are
+//! read when they are recognized and skipped otherwise, so `100n X7R` is still 100 nF.
+
+use std::fmt::Display;
+
+use crate::Component;
+
+/// The unit of a quantity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unit {
+    Ohm,
+    Farad,
+    Henry,
+    Hertz,
+    Volt,
+    Ampere,
+    Watt,
+}
+
+impl Unit {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Ohm => "Ω",
+            Unit::Farad => "F",
+            Unit::Henry => "H",
+            Unit::Hertz => "Hz",
+            Unit::Volt => "V",
+            Unit::Ampere => "A",
+            Unit::Watt => "W",
+        }
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "Ω" | "ohm" | "Ohm" | "ohms" | "Ohms" => Some(Unit::Ohm),
+            "F" | "f" => Some(Unit::Farad),
+            "H" => Some(Unit::Henry),
+            "Hz" | "hz" => Some(Unit::Hertz),
+            "V" | "v" => Some(Unit::Volt),
+            "A" => Some(Unit::Ampere),
+            "W" => Some(Unit::Watt),
+            _ => None,
+        }
+    }
+
+    /// The unit of the value of a component, from its part name or else its reference designator prefix
+    pub fn infer(comp: &Component<'_>) -> Option<Self> {
+        let part = comp.part_id.part.split('_').next().unwrap_or_default();
+        let by_part = match part {
+            "R" | "RN" => Some(Unit::Ohm),
+            "C" | "CP" => Some(Unit::Farad),
+            "L" => Some(Unit::Henry),
+            "Crystal" | "Resonator" => Some(Unit::Hertz),
+            _ => None,
+        };
+        by_part.or(match comp.ref_des.prefix() {
+            "R" | "RN" => Some(Unit::Ohm),
+            "C" => Some(Unit::Farad),
+            "L" => Some(Unit::Henry),
+            "X" | "Y" => Some(Unit::Hertz),
+            _ => None,
+        })
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// A parsed component value
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quantity {
+    /// The value in the base unit, such as farads rather than microfarads
+    pub value: f64,
+    /// The unit given in the value or inferred from the component
+    pub unit: Option<Unit>,
+    /// The tolerance as a fraction, so `1%` is 0.01
+    pub tolerance: Option<f64>,
+    /// The voltage rating in volts
+    pub voltage: Option<f64>,
+}
+
+impl Quantity {
+    /// Parse a value on its own, without a unit unless it is part of the value
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut words = value
+            .split(['/', ' ', '\t'])
+            .filter(|word| !word.is_empty());
+        let (value, unit) = parse_number(words.next()?)?;
+        let mut quantity = Quantity {
+            value,
+            unit,
+            tolerance: None,
+            voltage: None,
+        };
+        for word in words {
+            if let Some(percent) = word.trim_start_matches(['±', '+', '-']).strip_suffix('%') {
+                quantity.tolerance = percent.parse::<f64>().ok().map(|p| p / 100.0);
+            } else if let Some((voltage, Some(Unit::Volt))) = parse_number(word) {
+                quantity.voltage = Some(voltage);
+            }
+        }
+        Some(quantity)
+    }
+
+    /// Parse the value of a component, inferring the unit from the part when the value has none
+    pub fn of(comp: &Component<'_>) -> Option<Self> {
+        let mut quantity = Self::parse(comp.value.as_str())?;
+        if quantity.unit.is_none() {
+            quantity.unit = Unit::infer(comp);
+        }
+        Some(quantity)
+    }
+
+    /// Whether the quantity has the unit, or no unit at all
+    pub fn is(&self, unit: Unit) -> bool {
+        match self.unit {
+            Some(u) => u == unit,
+            None => true,
+        }
+    }
+
+    /// The value with an engineering prefix and the unit, such as `100nF` or `4.7kΩ`
+    ///
+    /// Values which are equal print the same, which makes this a key for grouping components.
+    pub fn normalized(&self) -> String {
+        const PREFIXES: [(f64, &str); 10] = [
+            (1e12, "T"),
+            (1e9, "G"),
+            (1e6, "M"),
+            (1e3, "k"),
+            (1.0, ""),
+            (1e-3, "m"),
+            (1e-6, "µ"),
+            (1e-9, "n"),
+            (1e-12, "p"),
+            (1e-15, "f"),
+        ];
+        let unit = self.unit.map_or("", |unit| unit.symbol());
+        let magnitude = self.value.abs();
+        if magnitude == 0.0 {
+            return format!("0{unit}");
+        }
+        // Anything smaller than the smallest prefix is still written with it
+        let (scale, prefix) = PREFIXES
+            .iter()
+            .find(|(scale, _)| magnitude >= scale * (1.0 - 1e-9))
+            .unwrap_or(&PREFIXES[PREFIXES.len() - 1]);
+        let mantissa = format!("{:.3}", self.value / scale);
+        let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+        format!("{mantissa}{prefix}{unit}")
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.normalized())?;
+        if let Some(voltage) = self.voltage {
+            write!(f, "/{voltage}V")?;
+        }
+        if let Some(tolerance) = self.tolerance {
+            write!(f, " {}%", tolerance * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a number such as `4.7uF`, `4k7` or `10R` into its value in the base unit and the unit, if any
+fn parse_number(word: &str) -> Option<(f64, Option<Unit>)> {
+    let digits = word
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(word.len());
+    let (number, rest) = word.split_at(digits);
+    if number.is_empty() {
+        return None;
+    }
+
+    let mut chars = rest.chars();
+    let (multiplier, marker, rest) = match chars.next().and_then(multiplier) {
+        Some((multiplier, marker)) => (multiplier, marker, chars.as_str()),
+        None => (1.0, None, rest),
+    };
+
+    // A prefix may stand in for the decimal point, as in `4k7` or `0R1`
+    let fraction = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (fraction, unit) = rest.split_at(fraction);
+    let number: f64 = if fraction.is_empty() {
+        number.parse().ok()?
+    } else if number.contains('.') {
+        return None;
+    } else {
+        format!("{number}.{fraction}").parse().ok()?
+    };
+
+    let unit = match unit {
+        "" => marker,
+        symbol => Some(Unit::from_symbol(symbol)?),
+    };
+    Some((number * multiplier, unit))
+}
+
+/// The multiplier of a prefix, and the unit it implies
+fn multiplier(prefix: char) -> Option<(f64, Option<Unit>)> {
+    let multiplier = match prefix {
+        'f' => 1e-15,
+        'p' => 1e-12,
+        'n' => 1e-9,
+        'u' | 'µ' | 'μ' => 1e-6,
+        'm' => 1e-3,
+        'R' | 'r' | 'E' => return Some((1.0, Some(Unit::Ohm))),
+        'k' | 'K' => 1e3,
+        'M' => 1e6,
+        'G' => 1e9,
+        _ => return None,
+    };
+    Some((multiplier, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NetList;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("100n", Some(100e-9), None)]
+    #[case("4.7uF", Some(4.7e-6), Some(Unit::Farad))]
+    #[case("2.2µF", Some(2.2e-6), Some(Unit::Farad))]
+    #[case("4μ7", Some(4.7e-6), None)]
+    #[case("4k7", Some(4700.0), None)]
+    #[case("10R", Some(10.0), Some(Unit::Ohm))]
+    #[case("0R", Some(0.0), Some(Unit::Ohm))]
+    #[case("1M", Some(1e6), None)]
+    #[case("16MHz", Some(16e6), Some(Unit::Hertz))]
+    #[case("100n X7R", Some(100e-9), None)]
+    #[case("22", Some(22.0), None)]
+    #[case("0.5pF", Some(0.5e-12), Some(Unit::Farad))]
+    #[case("10fF", Some(10e-15), Some(Unit::Farad))]
+    #[case("Chip", None, None)]
+    #[case("4.7k7", None, None)]
+    #[case("10uX", None, None)]
+    fn can_parse(#[case] value: &str, #[case] expected: Option<f64>, #[case] unit: Option<Unit>) {
+        let quantity = Quantity::parse(value);
+        match (quantity.map(|q| q.value), expected) {
+            (Some(a), Some(b)) => assert!((a - b).abs() <= b.abs() * 1e-9, "{a} != {b}"),
+            (a, b) => assert_eq!(a, b),
+        }
+        assert_eq!(quantity.and_then(|q| q.unit), unit);
+    }
+
+    #[test]
+    fn can_parse_ratings() {
+        let quantity = Quantity::parse("10u/25V").unwrap();
+        assert_eq!(quantity.voltage, Some(25.0));
+        assert_eq!(quantity.tolerance, None);
+
+        let quantity = Quantity::parse("4k7 ±1% 0603").unwrap();
+        assert_eq!(quantity.value, 4700.0);
+        assert_eq!(quantity.tolerance, Some(0.01));
+        assert_eq!(quantity.to_string(), "4.7k 1%");
+    }
+
+    #[rstest]
+    #[case("100nF", "100nF")]
+    #[case("0.1u", "100n")]
+    #[case("4.7uF", "4.7µF")]
+    #[case("4k7", "4.7k")]
+    #[case("4700R", "4.7kΩ")]
+    #[case("0R", "0Ω")]
+    #[case("1000p", "1n")]
+    #[case("0.5pF", "500fF")]
+    #[case("0.3pF", "300fF")]
+    #[case("0.5fF", "0.5fF")]
+    #[case("0pF", "0F")]
+    fn can_normalize(#[case] value: &str, #[case] expected: &str) {
+        assert_eq!(Quantity::parse(value).unwrap().normalized(), expected);
+    }
+
+    #[test]
+    fn infers_unit_from_part() {
+        let input = test_data!("analysis.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let quantity =
+            |ref_des: &str| Quantity::of(netlist.find_component(ref_des.into()).unwrap());
+
+        assert_eq!(quantity("C1").unwrap().normalized(), "100nF");
+        assert_eq!(quantity("C4").unwrap().normalized(), "100nF");
+        assert_eq!(quantity("C4").unwrap().voltage, Some(16.0));
+        assert_eq!(quantity("R1").unwrap().normalized(), "4.7kΩ");
+        assert_eq!(quantity("U1"), None);
+    }
+}
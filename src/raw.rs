@@ -3,11 +3,23 @@ mod parser;
 /// The full netlist
 #[derive(Debug, Clone)]
 pub struct NetList<'a> {
+    pub design: Design<'a>,
     pub components: Vec<Component<'a>>,
     pub parts: Vec<Part<'a>>,
     pub nets: Vec<Net<'a>>,
 }
 
+/// Information about the schematic the netlist was generated from
+#[derive(Debug, Clone, Default)]
+pub struct Design<'a> {
+    pub source: Option<&'a str>,
+    pub date: Option<&'a str>,
+    pub tool: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub company: Option<&'a str>,
+    pub rev: Option<&'a str>,
+}
+
 /// A component in the schematic
 #[derive(Debug, Clone)]
 pub struct Component<'a> {
@@ -16,7 +28,10 @@ pub struct Component<'a> {
     pub part: &'a str,
     pub lib: &'a str,
     pub properties: Vec<(&'a str, &'a str)>,
+    pub fields: Vec<(&'a str, &'a str)>,
     pub footprint: Option<&'a str>,
+    pub sheetpath: Option<(&'a str, &'a str)>,
+    pub tstamp: Option<&'a str>,
 }
 
 /// An indivudual pin
@@ -33,6 +48,7 @@ pub struct Part<'a> {
     pub part: &'a str,
     pub lib: &'a str,
     pub description: &'a str,
+    pub footprints: Vec<&'a str>,
     pub pins: Vec<Pin<'a>>,
 }
 
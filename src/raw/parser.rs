@@ -1,5 +1,5 @@
 use crate::error::ParseError;
-use crate::raw::{Component, Net, NetList, Node, Part, Pin};
+use crate::raw::{Component, Design, Net, NetList, Node, Part, Pin};
 use crate::sexpr::SExpr;
 
 impl<'a> TryFrom<&SExpr<'a>> for Component<'a> {
@@ -13,23 +13,78 @@ impl<'a> TryFrom<&SExpr<'a>> for Component<'a> {
             .children("property")
             .map(|prop| {
                 let name = prop.value("name")?;
-                let value = prop.value("value")?;
+                // Flags such as `exclude_from_board` are written without a value
+                let value = prop.value("value").unwrap_or_default();
                 Ok((name, value))
             })
             .collect::<Result<_, Self::Error>>()?;
 
+        let fields = if let Ok(fields) = value.child("fields") {
+            fields
+                .children("field")
+                .map(|field| {
+                    let name = field.value("name")?;
+                    let value = field.string().unwrap_or_default();
+                    Ok((name, value))
+                })
+                .collect::<Result<_, Self::Error>>()?
+        } else {
+            vec![]
+        };
+
         let (lib, part) = {
             let libsource = value.child("libsource")?;
             (libsource.value("lib")?, libsource.value("part")?)
         };
 
+        let sheetpath = value
+            .child("sheetpath")
+            .and_then(|path| Ok((path.value("names")?, path.value("tstamps")?)))
+            .ok();
+        let tstamp = value
+            .value("tstamps")
+            .or_else(|_| value.value("tstamp"))
+            .ok();
+
         Ok(Self {
             ref_des,
             value: val,
             part,
             lib,
             properties,
+            fields,
             footprint,
+            sheetpath,
+            tstamp,
+        })
+    }
+}
+
+impl<'a> TryFrom<&SExpr<'a>> for Design<'a> {
+    type Error = ParseError;
+
+    fn try_from(value: &SExpr<'a>) -> Result<Self, Self::Error> {
+        let source = value.value("source").ok();
+        let date = value.value("date").ok();
+        let tool = value.value("tool").ok();
+
+        let title_block = value
+            .children("sheet")
+            .next()
+            .and_then(|sheet| sheet.child("title_block").ok());
+        let title_value = |label| {
+            title_block
+                .and_then(|block| block.value(label).ok())
+                .filter(|s| !s.is_empty())
+        };
+
+        Ok(Design {
+            source,
+            date,
+            tool,
+            title: title_value("title"),
+            company: title_value("company"),
+            rev: title_value("rev"),
         })
     }
 }
@@ -53,6 +108,14 @@ impl<'a> TryFrom<&SExpr<'a>> for Part<'a> {
         let lib = value.value("lib")?;
         let part = value.value("part")?;
         let description = value.value("description").unwrap_or_default();
+        let footprints = if let Ok(footprints) = value.child("footprints") {
+            footprints
+                .children("fp")
+                .filter_map(|fp| fp.string())
+                .collect()
+        } else {
+            vec![]
+        };
         let pins = if let Ok(pins) = value.child("pins") {
             pins.children("pin")
                 .map(|pin| pin.try_into())
@@ -64,6 +127,7 @@ impl<'a> TryFrom<&SExpr<'a>> for Part<'a> {
             part,
             lib,
             description,
+            footprints,
             pins,
         })
     }
@@ -115,6 +179,11 @@ impl<'a> TryFrom<&SExpr<'a>> for NetList<'a> {
             return Err(ParseError::UnknownVersion(version.to_owned()));
         };
 
+        let design = match value.child("design") {
+            Ok(design) => design.try_into()?,
+            Err(_) => Design::default(),
+        };
+
         let components: Vec<Component<'a>> = value
             .child("components")?
             .children("comp")
@@ -134,6 +203,7 @@ impl<'a> TryFrom<&SExpr<'a>> for NetList<'a> {
             .collect::<Result<_, _>>()?;
 
         Ok(NetList {
+            design,
             components,
             parts,
             nets,
@@ -169,17 +239,6 @@ impl<'a> TryFrom<&'a String> for NetList<'a> {
 mod tests {
     use super::*;
 
-    macro_rules! test_data {
-        ($fname:expr) => {
-            std::fs::read_to_string(concat!(
-                env!("CARGO_MANIFEST_DIR"),
-                "/resources/test/",
-                $fname
-            ))
-            .unwrap()
-        };
-    }
-
     #[test]
     fn can_parse_comp() {
         let i = &test_data!("kvt.net");
@@ -235,4 +294,18 @@ mod tests {
         assert_eq!(netlist.parts.len(), 3);
         assert_eq!(netlist.nets.len(), 7);
     }
+
+    #[test]
+    fn property_value_is_optional() {
+        // KiCad writes flags such as `exclude_from_board` as a property without a value
+        let input = "(comp (ref \"R1\") (value \"10k\") (libsource (lib \"Device\") (part \"R\")) \
+                     (property (name \"exclude_from_board\")) (property (name \"MPN\") (value \"RC0603\")))";
+        let root = SExpr::try_from(input).unwrap();
+        let comp: Component = (&root).try_into().unwrap();
+
+        assert_eq!(
+            comp.properties,
+            vec![("exclude_from_board", ""), ("MPN", "RC0603")]
+        );
+    }
 }
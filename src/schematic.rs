@@ -0,0 +1,707 @@
+//! Build a netlist directly from KiCad schematic files
+//!
+//! The schematic hierarchy is loaded from the root `.kicad_sch` file, and the connectivity is computed in the same way
+//! as Eeschema does it:
+//!
+//! - Wires connect to everything lying on them, but crossing wires are only connected by a junction.
+//! - Local labels connect within a sheet, global labels and power symbols connect everywhere, and hierarchical labels
+//!   connect to the pins of the sheet symbol in the parent sheet.
+//! - Hidden power input pins connect to the global net with the same name as the pin.
+//! - Buses connect their members through sheet pins, member by member. Members within a sheet are connected by their
+//!   labels, so bus entries are only graphical.
+//! - Pins with a no connect flag get the `+no_connect` suffix on their pin type.
+//!
+//! Nets are named after their strongest driver, with the same priorities as Eeschema: global labels, power pins, local
+//! labels, hierarchical labels, sheet pins and finally component pins.
+//!
+//! ```no_run
+//! use kicad_netlist::{schematic::Schematic, NetList};
+//!
+//! let schematic = Schematic::load("project.kicad_sch").unwrap();
+//! let text = schematic.to_netlist();
+//! let netlist = NetList::parse(&text).unwrap();
+//! ```
+
+mod netlist;
+
+use std::path::{Path, PathBuf};
+
+use crate::sexpr::SExpr;
+use crate::SchematicError;
+
+/// A schematic hierarchy
+///
+/// All sheet files are read and checked when the schematic is loaded.
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    files: Vec<SheetFile>,
+}
+
+#[derive(Debug, Clone)]
+struct SheetFile {
+    /// The file name as it is referenced by the parent sheet
+    name: String,
+    path: PathBuf,
+    text: String,
+}
+
+impl Schematic {
+    /// Load the root sheet and every sheet below it
+    ///
+    /// Sheet files are looked up relative to the directory of the root sheet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SchematicError> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut files = vec![];
+        let mut queue = vec![name];
+        while let Some(name) = queue.pop() {
+            if files.iter().any(|file: &SheetFile| file.name == name) {
+                continue;
+            }
+            let path = dir.join(&name);
+            let text = std::fs::read_to_string(&path).map_err(|source| SchematicError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            let sheet = parse_file(&path, &text)?;
+            queue.extend(sheet.sheets.iter().map(|sheet| sheet.file.to_owned()));
+            files.push(SheetFile { name, path, text });
+        }
+
+        // Check that the hierarchy can be built, so that generating the netlist cannot fail later
+        let schematic = Self { files };
+        schematic.parse()?;
+        Ok(schematic)
+    }
+
+    /// Generate a KiCad netlist for the schematic
+    ///
+    /// The result can be parsed with [`NetList::parse`](crate::NetList::parse).
+    pub fn to_netlist(&self) -> String {
+        let (sheets, instances) = self
+            .parse()
+            .expect("the schematic was checked when it was loaded");
+        let files: Vec<&str> = self.files.iter().map(|file| file.name.as_str()).collect();
+        netlist::write(&files, &sheets, &instances)
+    }
+
+    fn parse(&self) -> Result<(Vec<Sheet<'_>>, Vec<Instance>), SchematicError> {
+        let sheets = self
+            .files
+            .iter()
+            .map(|file| parse_file(&file.path, &file.text))
+            .collect::<Result<Vec<_>, _>>()?;
+        let instances = self.instances(&sheets)?;
+        for instance in instances.iter() {
+            let sheet = &sheets[instance.file];
+            for symbol in sheet.symbols.iter() {
+                if !sheet
+                    .lib_symbols
+                    .iter()
+                    .any(|lib| lib.name == symbol.lib_key())
+                {
+                    return Err(SchematicError::MissingLibSymbol(
+                        symbol.lib_key().to_owned(),
+                    ));
+                }
+            }
+        }
+        Ok((sheets, instances))
+    }
+
+    /// Walk the hierarchy and list every use of a sheet
+    fn instances(&self, sheets: &[Sheet<'_>]) -> Result<Vec<Instance>, SchematicError> {
+        let root = Instance {
+            file: 0,
+            parent: None,
+            depth: 0,
+            sheet_name: String::new(),
+            names: "/".to_owned(),
+            tstamps: "/".to_owned(),
+            path: format!("/{}", sheets[0].uuid),
+        };
+
+        let mut instances = vec![root];
+        let mut i = 0;
+        while i < instances.len() {
+            let parent = &instances[i];
+            let mut children = vec![];
+            for (index, sheet) in sheets[parent.file].sheets.iter().enumerate() {
+                let file = self
+                    .files
+                    .iter()
+                    .position(|file| file.name == sheet.file)
+                    .expect("all sheet files are loaded");
+
+                let mut ancestor = Some(i);
+                while let Some(a) = ancestor {
+                    if instances[a].file == file {
+                        return Err(SchematicError::RecursiveSheet(sheet.file.to_owned()));
+                    }
+                    ancestor = instances[a].parent.map(|(p, _)| p);
+                }
+
+                children.push(Instance {
+                    file,
+                    parent: Some((i, index)),
+                    depth: parent.depth + 1,
+                    sheet_name: sheet.name.to_owned(),
+                    names: format!("{}{}/", parent.names, sheet.name),
+                    tstamps: format!("{}{}/", parent.tstamps, sheet.uuid),
+                    path: format!("{}/{}", parent.path, sheet.uuid),
+                });
+            }
+            instances.extend(children);
+            i += 1;
+        }
+        Ok(instances)
+    }
+}
+
+/// One use of a sheet file in the hierarchy
+#[derive(Debug, Clone)]
+struct Instance {
+    file: usize,
+    /// The parent instance and the index of the sheet symbol in it
+    parent: Option<(usize, usize)>,
+    depth: usize,
+    sheet_name: String,
+    /// The human readable sheet path, such as `/power/`
+    names: String,
+    /// The sheet path made of sheet uuids, as used in the netlist
+    tstamps: String,
+    /// The sheet path starting with the root uuid, as used in symbol instances
+    path: String,
+}
+
+/// A position in schematic units of 0.1 µm
+type Point = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LabelKind {
+    Local,
+    Global,
+    Hierarchical,
+}
+
+#[derive(Debug, Clone)]
+struct Label<'a> {
+    kind: LabelKind,
+    text: &'a str,
+    at: Point,
+}
+
+#[derive(Debug, Clone)]
+struct LibPin<'a> {
+    num: &'a str,
+    name: &'a str,
+    typ: &'a str,
+    unit: u32,
+    style: u32,
+    hidden: bool,
+    at: Point,
+}
+
+#[derive(Debug, Clone)]
+struct LibSymbol<'a> {
+    name: &'a str,
+    power: bool,
+    properties: Vec<(&'a str, &'a str)>,
+    pins: Vec<LibPin<'a>>,
+}
+
+impl<'a> LibSymbol<'a> {
+    fn property(&self, name: &str) -> Option<&'a str> {
+        find_property(&self.properties, name)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SymbolInstance<'a> {
+    /// The full path starting with the root uuid
+    path: &'a str,
+    reference: &'a str,
+    unit: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct Symbol<'a> {
+    lib_id: &'a str,
+    lib_name: Option<&'a str>,
+    at: Point,
+    angle: i64,
+    mirror: Option<&'a str>,
+    unit: u32,
+    style: u32,
+    in_bom: bool,
+    dnp: bool,
+    uuid: &'a str,
+    properties: Vec<(&'a str, &'a str)>,
+    instances: Vec<SymbolInstance<'a>>,
+}
+
+impl<'a> Symbol<'a> {
+    fn property(&self, name: &str) -> Option<&'a str> {
+        find_property(&self.properties, name)
+    }
+
+    /// The name of the symbol in the `lib_symbols` of the sheet
+    fn lib_key(&self) -> &'a str {
+        self.lib_name.unwrap_or(self.lib_id)
+    }
+
+    /// The reference and unit of the symbol in a sheet instance
+    fn reference(&self, instance: &Instance) -> (&'a str, u32) {
+        match self
+            .instances
+            .iter()
+            .find(|inst| inst.path == instance.path)
+        {
+            Some(inst) => (inst.reference, inst.unit.unwrap_or(self.unit)),
+            None => (self.property("Reference").unwrap_or_default(), self.unit),
+        }
+    }
+
+    /// Place a pin of the library symbol on the sheet
+    ///
+    /// Library symbols have the y axis pointing up. The symbol is rotated counterclockwise and then mirrored.
+    fn place(&self, pin: Point) -> Point {
+        let (x, y) = (pin.0, -pin.1);
+        let (x, y) = match self.angle.rem_euclid(360) {
+            90 => (y, -x),
+            180 => (-x, -y),
+            270 => (-y, x),
+            _ => (x, y),
+        };
+        let (x, y) = match self.mirror {
+            Some("x") => (x, -y),
+            Some("y") => (-x, y),
+            _ => (x, y),
+        };
+        (self.at.0 + x, self.at.1 + y)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SheetPin<'a> {
+    name: &'a str,
+    at: Point,
+}
+
+#[derive(Debug, Clone)]
+struct SheetSymbol<'a> {
+    name: &'a str,
+    file: &'a str,
+    uuid: &'a str,
+    pins: Vec<SheetPin<'a>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TitleBlock<'a> {
+    title: &'a str,
+    company: &'a str,
+    rev: &'a str,
+    date: &'a str,
+}
+
+/// The contents of a schematic file
+#[derive(Debug, Clone)]
+struct Sheet<'a> {
+    uuid: &'a str,
+    title_block: TitleBlock<'a>,
+    lib_symbols: Vec<LibSymbol<'a>>,
+    symbols: Vec<Symbol<'a>>,
+    wires: Vec<(Point, Point)>,
+    buses: Vec<(Point, Point)>,
+    junctions: Vec<Point>,
+    no_connects: Vec<Point>,
+    labels: Vec<Label<'a>>,
+    sheets: Vec<SheetSymbol<'a>>,
+}
+
+impl<'a> Sheet<'a> {
+    fn lib_symbol(&self, symbol: &Symbol<'_>) -> &LibSymbol<'a> {
+        self.lib_symbols
+            .iter()
+            .find(|lib| lib.name == symbol.lib_key())
+            .expect("lib symbols are checked when the schematic is loaded")
+    }
+}
+
+fn find_property<'a>(properties: &[(&'a str, &'a str)], name: &str) -> Option<&'a str> {
+    properties
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, value)| *value)
+}
+
+fn parse_file<'a>(path: &Path, text: &'a str) -> Result<Sheet<'a>, SchematicError> {
+    let sexpr = SExpr::try_from(text).map_err(|source| SchematicError::Parse {
+        path: path.to_owned(),
+        source,
+    })?;
+    sheet(&sexpr).map_err(|err| match err {
+        Error::Parse(source) => SchematicError::Parse {
+            path: path.to_owned(),
+            source,
+        },
+        Error::Schematic(err) => err,
+    })
+}
+
+/// Errors while reading a sheet, before the path of the file is known
+enum Error {
+    Parse(crate::ParseError),
+    Schematic(SchematicError),
+}
+
+impl From<crate::ParseError> for Error {
+    fn from(value: crate::ParseError) -> Self {
+        Error::Parse(value)
+    }
+}
+
+fn number(s: &str) -> Result<f64, Error> {
+    s.parse()
+        .map_err(|_| Error::Schematic(SchematicError::InvalidNumber(s.to_owned())))
+}
+
+/// Convert millimeters to schematic units, so that positions can be compared exactly
+fn coord(s: &str) -> Result<i64, Error> {
+    Ok((number(s)? * 10000.0).round() as i64)
+}
+
+fn point(value: &SExpr<'_>) -> Result<Point, Error> {
+    let mut strings = value.strings();
+    let x = strings.next().ok_or(crate::ParseError::MissingValue())?;
+    let y = strings.next().ok_or(crate::ParseError::MissingValue())?;
+    Ok((coord(x)?, coord(y)?))
+}
+
+fn at(value: &SExpr<'_>) -> Result<Point, Error> {
+    point(value.child("at")?)
+}
+
+fn properties<'a>(value: &SExpr<'a>) -> Vec<(&'a str, &'a str)> {
+    value
+        .children("property")
+        .map(|prop| {
+            let mut strings = prop.strings();
+            (
+                strings.next().unwrap_or_default(),
+                strings.next().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+/// KiCad 7 marks hidden items with a bare `hide`, later versions use `(hide yes)`
+fn is_hidden(value: &SExpr<'_>) -> bool {
+    value.strings().any(|s| s == "hide") || value.value("hide").is_ok_and(|hide| hide == "yes")
+}
+
+fn yes(value: &SExpr<'_>, label: &str, default: bool) -> bool {
+    value.value(label).map_or(default, |v| v == "yes")
+}
+
+fn segments(value: &SExpr<'_>, label: &str) -> Result<Vec<(Point, Point)>, Error> {
+    let mut segments = vec![];
+    for wire in value.children(label) {
+        let pts = wire
+            .child("pts")?
+            .children("xy")
+            .map(point)
+            .collect::<Result<Vec<_>, _>>()?;
+        segments.extend(pts.windows(2).map(|w| (w[0], w[1])));
+    }
+    Ok(segments)
+}
+
+fn lib_symbol<'a>(value: &SExpr<'a>) -> Result<LibSymbol<'a>, Error> {
+    let name = value.string().ok_or(crate::ParseError::MissingValue())?;
+    let mut pins = vec![];
+    for sub in value.children("symbol") {
+        // Sub symbols are named `<name>_<unit>_<style>`
+        let mut parts = sub.string().unwrap_or_default().rsplit('_');
+        let style = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let unit = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        for pin in sub.children("pin") {
+            pins.push(LibPin {
+                num: pin.value("number")?,
+                name: pin.value("name")?,
+                typ: pin.string().ok_or(crate::ParseError::MissingValue())?,
+                unit,
+                style,
+                hidden: is_hidden(pin),
+                at: at(pin)?,
+            });
+        }
+    }
+    Ok(LibSymbol {
+        name,
+        power: value.children("power").next().is_some(),
+        properties: properties(value),
+        pins,
+    })
+}
+
+fn symbol<'a>(value: &SExpr<'a>) -> Result<Symbol<'a>, Error> {
+    let position = value.child("at")?;
+    let angle = position.strings().nth(2).map(number).transpose()?;
+
+    let mut instances = vec![];
+    if let Ok(list) = value.child("instances") {
+        for project in list.children("project") {
+            for path in project.children("path") {
+                instances.push(SymbolInstance {
+                    path: path.string().unwrap_or_default(),
+                    reference: path.value("reference")?,
+                    unit: path.value("unit").ok().and_then(|unit| unit.parse().ok()),
+                });
+            }
+        }
+    }
+
+    Ok(Symbol {
+        lib_id: value.value("lib_id")?,
+        lib_name: value.value("lib_name").ok(),
+        at: point(position)?,
+        angle: angle.unwrap_or_default().round() as i64,
+        mirror: value.value("mirror").ok(),
+        unit: value
+            .value("unit")
+            .ok()
+            .and_then(|unit| unit.parse().ok())
+            .unwrap_or(1),
+        style: value
+            .value("convert")
+            .or_else(|_| value.value("body_style"))
+            .ok()
+            .and_then(|style| style.parse().ok())
+            .unwrap_or(1),
+        in_bom: yes(value, "in_bom", true),
+        dnp: yes(value, "dnp", false),
+        uuid: value.value("uuid").unwrap_or_default(),
+        properties: properties(value),
+        instances,
+    })
+}
+
+fn sheet_symbol<'a>(value: &SExpr<'a>) -> Result<SheetSymbol<'a>, Error> {
+    let properties = properties(value);
+    // KiCad 6 used `Sheet name` and `Sheet file`
+    let property = |names: [&str; 2]| {
+        names
+            .iter()
+            .find_map(|name| find_property(&properties, name))
+            .unwrap_or_default()
+    };
+    let pins = value
+        .children("pin")
+        .map(|pin| {
+            Ok(SheetPin {
+                name: pin.string().unwrap_or_default(),
+                at: at(pin)?,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(SheetSymbol {
+        name: property(["Sheetname", "Sheet name"]),
+        file: property(["Sheetfile", "Sheet file"]),
+        uuid: value.value("uuid").unwrap_or_default(),
+        pins,
+    })
+}
+
+fn sheet<'a>(value: &SExpr<'a>) -> Result<Sheet<'a>, Error> {
+    let label = value.label().unwrap_or_default();
+    if label != "kicad_sch" {
+        return Err(crate::ParseError::UnexpectedRootLabel(label.to_owned()).into());
+    }
+
+    let title_block = match value.child("title_block") {
+        Ok(block) => {
+            let field = |label| block.value(label).unwrap_or_default();
+            TitleBlock {
+                title: field("title"),
+                company: field("company"),
+                rev: field("rev"),
+                date: field("date"),
+            }
+        }
+        Err(_) => TitleBlock::default(),
+    };
+
+    let lib_symbols = match value.child("lib_symbols") {
+        Ok(libs) => libs
+            .children("symbol")
+            .map(lib_symbol)
+            .collect::<Result<_, _>>()?,
+        Err(_) => vec![],
+    };
+
+    let mut labels = vec![];
+    for (label, kind) in [
+        ("label", LabelKind::Local),
+        ("global_label", LabelKind::Global),
+        ("hierarchical_label", LabelKind::Hierarchical),
+    ] {
+        for l in value.children(label) {
+            labels.push(Label {
+                kind,
+                text: l.string().ok_or(crate::ParseError::MissingValue())?,
+                at: at(l)?,
+            });
+        }
+    }
+
+    Ok(Sheet {
+        uuid: value.value("uuid").unwrap_or_default(),
+        title_block,
+        lib_symbols,
+        symbols: value
+            .children("symbol")
+            .map(symbol)
+            .collect::<Result<_, _>>()?,
+        wires: segments(value, "wire")?,
+        buses: segments(value, "bus")?,
+        junctions: value
+            .children("junction")
+            .map(at)
+            .collect::<Result<_, _>>()?,
+        no_connects: value
+            .children("no_connect")
+            .map(at)
+            .collect::<Result<_, _>>()?,
+        labels,
+        sheets: value
+            .children("sheet")
+            .map(sheet_symbol)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NetList;
+
+    macro_rules! test_path {
+        ($fname:expr) => {
+            concat!(env!("CARGO_MANIFEST_DIR"), "/resources/test/", $fname)
+        };
+    }
+
+    #[test]
+    fn kvt_matches_eeschema_export() {
+        let schematic = Schematic::load(test_path!("kvt.kicad_sch")).unwrap();
+        let text = schematic.to_netlist();
+        let netlist = NetList::parse(&text).unwrap();
+
+        let expected = test_data!("kvt.net");
+        let expected = NetList::parse(&expected).unwrap();
+
+        assert_eq!(netlist.nets, expected.nets);
+        assert_eq!(netlist.parts, expected.parts);
+        assert_eq!(netlist.components.len(), expected.components.len());
+        for (comp, expected) in netlist.components.iter().zip(expected.components.iter()) {
+            assert_eq!(comp.ref_des, expected.ref_des);
+            assert_eq!(comp.value, expected.value);
+            assert_eq!(comp.part_id, expected.part_id);
+            assert_eq!(comp.footprint, expected.footprint);
+            assert_eq!(comp.fields, expected.fields);
+            assert_eq!(comp.pins, expected.pins);
+            assert_eq!(comp.sheet_path, expected.sheet_path);
+            assert_eq!(comp.tstamp, expected.tstamp);
+            for prop in expected.properties.iter() {
+                assert_eq!(comp.find_property(prop.name), Some(prop.value));
+            }
+        }
+        assert!(crate::equiv::equivalent(&netlist, &expected).is_ok());
+    }
+
+    #[test]
+    fn can_connect_hierarchy() {
+        let schematic = Schematic::load(test_path!("hier/hier.kicad_sch")).unwrap();
+        let text = schematic.to_netlist();
+        let netlist = NetList::parse(&text).unwrap();
+
+        let nets: Vec<(&str, Vec<String>)> = netlist
+            .nets
+            .iter()
+            .map(|net| {
+                let nodes = net
+                    .nodes
+                    .iter()
+                    .map(|node| format!("{}.{}:{}", node.ref_des, node.num, node.typ))
+                    .collect();
+                (net.name.as_str(), nodes)
+            })
+            .collect();
+        let expected: Vec<(&str, Vec<&str>)> = vec![
+            ("/A/IN", vec!["J1.1:passive", "R1.1:passive"]),
+            ("/B/OUT", vec!["J1.2:passive", "R4.2:passive"]),
+            ("/MID", vec!["R1.2:passive", "R4.1:passive"]),
+            ("/P0", vec!["J1.5:passive", "R2.1:passive", "R5.1:passive"]),
+            ("/P1", vec!["J1.6:passive", "R3.1:passive", "R6.1:passive"]),
+            ("CLK", vec!["J1.4:passive", "R2.2:passive", "R5.2:passive"]),
+            ("unconnected-(J1-Pin_3)", vec!["J1.3:no_connect"]),
+            ("unconnected-(R3-Pad2)", vec!["R3.2:passive"]),
+            ("unconnected-(R6-Pad2)", vec!["R6.2:passive"]),
+        ];
+        let expected: Vec<(&str, Vec<String>)> = expected
+            .into_iter()
+            .map(|(name, nodes)| (name, nodes.into_iter().map(str::to_owned).collect()))
+            .collect();
+        assert_eq!(nets, expected);
+
+        let r5 = netlist.find_component("R5".into()).unwrap();
+        assert_eq!(r5.value, "1k".into());
+        assert_eq!(r5.sheet_path.as_ref().map(|path| path.names), Some("/B/"));
+        assert_eq!(r5.find_property("Sheetfile"), Some("stage.kicad_sch"));
+        assert_eq!(netlist.design.title, Some("Hierarchy test"));
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        let schematic = Schematic::load(test_path!("kvt.kicad_sch")).unwrap();
+        let (mut sheets, instances) = schematic.parse().unwrap();
+        let r1 = sheets[0]
+            .symbols
+            .iter_mut()
+            .find(|symbol| find_property(&symbol.properties, "Reference") == Some("R1"))
+            .unwrap();
+        r1.properties.push(("Note", r#"use "1%" or \ better"#));
+        let text = netlist::write(&[r"C:\kicad\kvt.kicad_sch"], &sheets, &instances);
+        let netlist = NetList::parse(&text).unwrap();
+
+        assert_eq!(netlist.design.source, Some(r"C:\\kicad\\kvt.kicad_sch"));
+        let r1 = netlist.find_component("R1".into()).unwrap();
+        assert_eq!(r1.find_field("Note"), Some(r#"use \"1%\" or \\ better"#));
+    }
+
+    #[test]
+    fn missing_sheet_is_an_error() {
+        let dir = std::env::temp_dir().join("kicad_netlist_missing_sheet");
+        std::fs::create_dir_all(&dir).unwrap();
+        let root = dir.join("root.kicad_sch");
+        std::fs::write(
+            &root,
+            r#"(kicad_sch (version 20230121) (uuid 1)
+                (sheet (at 0 0) (size 10 10) (uuid 2)
+                  (property "Sheetname" "sub" (at 0 0 0))
+                  (property "Sheetfile" "missing.kicad_sch" (at 0 0 0))))"#,
+        )
+        .unwrap();
+
+        match Schematic::load(&root) {
+            Err(SchematicError::Io { path, .. }) => assert!(path.ends_with("missing.kicad_sch")),
+            result => panic!("Expected an error, got {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,597 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::{Instance, LabelKind, LibSymbol, Point, Sheet, Symbol};
+use crate::natural::natural_cmp;
+use crate::sexpr::quoted;
+
+/// Something which can be connected
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    /// A point on the wires of a sheet instance
+    Wire(usize, Point),
+    /// A point on the buses of a sheet instance
+    Bus(usize, Point),
+    /// A local or hierarchical label name in a sheet instance
+    Local(usize, String),
+    /// A global label or power net name
+    Global(String),
+    /// A placed pin
+    Pin(usize),
+}
+
+/// Union-find over everything which can be connected
+#[derive(Debug, Default)]
+struct Connectivity {
+    keys: HashMap<Key, usize>,
+    parent: Vec<usize>,
+}
+
+impl Connectivity {
+    fn node(&mut self, key: Key) -> usize {
+        let next = self.parent.len();
+        let node = *self.keys.entry(key).or_insert(next);
+        if node == next {
+            self.parent.push(next);
+        }
+        node
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            self.parent[node] = self.parent[self.parent[node]];
+            node = self.parent[node];
+        }
+        node
+    }
+
+    fn root(&mut self, key: Key) -> usize {
+        let node = self.node(key);
+        self.find(node)
+    }
+
+    fn union(&mut self, a: Key, b: Key) {
+        let a = self.root(a);
+        let b = self.root(b);
+        if a != b {
+            self.parent[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+/// A candidate for naming a net
+#[derive(Debug, Clone)]
+struct Driver {
+    priority: u8,
+    depth: usize,
+    passive: bool,
+    name: String,
+}
+
+impl Driver {
+    const GLOBAL: u8 = 6;
+    const POWER: u8 = 5;
+    const LOCAL: u8 = 4;
+    const HIERARCHICAL: u8 = 3;
+    const SHEET_PIN: u8 = 2;
+    const PIN: u8 = 1;
+
+    /// Stronger drivers compare greater
+    fn strength(&self, other: &Driver) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then(other.depth.cmp(&self.depth))
+            .then(other.passive.cmp(&self.passive))
+            .then_with(|| natural_cmp(&other.name, &self.name))
+    }
+}
+
+/// A library pin placed on a sheet instance
+#[derive(Debug, Clone)]
+struct PlacedPin<'a> {
+    ref_des: &'a str,
+    num: &'a str,
+    name: &'a str,
+    typ: &'a str,
+    no_connect: bool,
+}
+
+impl PlacedPin<'_> {
+    /// The pin name, or nothing if the pin is unnamed
+    fn function(&self) -> Option<&str> {
+        Some(self.name).filter(|name| !name.is_empty() && *name != "~")
+    }
+
+    /// The pin as Eeschema names it in generated net names
+    fn label(&self) -> String {
+        match self.function() {
+            Some(name) => format!("{}-{}", self.ref_des, name),
+            None => format!("{}-Pad{}", self.ref_des, self.num),
+        }
+    }
+
+    fn is_real(&self) -> bool {
+        !self.ref_des.starts_with('#')
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Comp<'a> {
+    ref_des: &'a str,
+    instance: &'a Instance,
+    symbol: &'a Symbol<'a>,
+    lib: &'a LibSymbol<'a>,
+}
+
+/// Expand bus syntax, such as `D[0..7]` or `I2C{SCL SDA}`, into the member names
+fn bus_members(text: &str) -> Option<Vec<String>> {
+    if let Some((prefix, rest)) = text.split_once('{') {
+        let group = rest.strip_suffix('}')?;
+        let mut members = vec![];
+        for member in group.split_whitespace() {
+            let expanded = bus_members(member).unwrap_or_else(|| vec![member.to_owned()]);
+            members.extend(expanded.into_iter().map(|m| match prefix {
+                "" => m,
+                prefix => format!("{prefix}.{m}"),
+            }));
+        }
+        return Some(members);
+    }
+
+    let (prefix, range) = text.split_once('[')?;
+    let (start, end) = range.strip_suffix(']')?.split_once("..")?;
+    let start: i64 = start.parse().ok()?;
+    let end: i64 = end.parse().ok()?;
+    let members = if start <= end {
+        (start..=end).map(|i| format!("{prefix}{i}")).collect()
+    } else {
+        (end..=start)
+            .rev()
+            .map(|i| format!("{prefix}{i}"))
+            .collect()
+    };
+    Some(members)
+}
+
+fn on_segment(p: Point, (a, b): (Point, Point)) -> bool {
+    let cross = (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+    cross == 0
+        && a.0.min(b.0) <= p.0
+        && p.0 <= a.0.max(b.0)
+        && a.1.min(b.1) <= p.1
+        && p.1 <= a.1.max(b.1)
+}
+
+/// Connect every point to the segments it lies on
+///
+/// Only the given points are considered, so segments crossing each other are not connected.
+fn connect_segments(
+    conn: &mut Connectivity,
+    segments: &[(Point, Point)],
+    points: &[Point],
+    key: impl Fn(Point) -> Key,
+) {
+    for &segment in segments.iter() {
+        for &point in points.iter().filter(|&&point| on_segment(point, segment)) {
+            conn.union(key(segment.0), key(point));
+        }
+    }
+}
+
+pub(super) fn write<'a>(
+    files: &[&str],
+    sheets: &'a [Sheet<'a>],
+    instances: &'a [Instance],
+) -> String {
+    let mut conn = Connectivity::default();
+    let mut drivers: Vec<(Key, Driver)> = vec![];
+    let mut pins: Vec<PlacedPin<'a>> = vec![];
+    let mut comps: Vec<Comp<'a>> = vec![];
+
+    for (i, instance) in instances.iter().enumerate() {
+        let sheet = &sheets[instance.file];
+        let mut wire_points: Vec<Point> = sheet.wires.iter().flat_map(|&(a, b)| [a, b]).collect();
+        let mut bus_points: Vec<Point> = sheet.buses.iter().flat_map(|&(a, b)| [a, b]).collect();
+        wire_points.extend(sheet.junctions.iter().copied());
+
+        let driver = |priority, name| Driver {
+            priority,
+            depth: instance.depth,
+            passive: false,
+            name,
+        };
+
+        for label in sheet.labels.iter() {
+            if bus_members(label.text).is_some() {
+                bus_points.push(label.at);
+                continue;
+            }
+            wire_points.push(label.at);
+            let text = label.text.to_owned();
+            let local = format!("{}{}", instance.names, label.text);
+            let (key, driver) = match label.kind {
+                LabelKind::Global => (Key::Global(text.clone()), driver(Driver::GLOBAL, text)),
+                LabelKind::Local => (Key::Local(i, text), driver(Driver::LOCAL, local)),
+                LabelKind::Hierarchical => {
+                    (Key::Local(i, text), driver(Driver::HIERARCHICAL, local))
+                }
+            };
+            conn.union(Key::Wire(i, label.at), key.clone());
+            drivers.push((key, driver));
+        }
+
+        for (c, child) in instances.iter().enumerate() {
+            let Some((_, index)) = child.parent.filter(|&(parent, _)| parent == i) else {
+                continue;
+            };
+            for pin in sheet.sheets[index].pins.iter() {
+                if bus_members(pin.name).is_some() {
+                    bus_points.push(pin.at);
+                    continue;
+                }
+                wire_points.push(pin.at);
+                let key = Key::Local(c, pin.name.to_owned());
+                conn.union(Key::Wire(i, pin.at), key.clone());
+                let name = format!("{}{}", instance.names, pin.name);
+                drivers.push((key, driver(Driver::SHEET_PIN, name)));
+            }
+        }
+
+        for symbol in sheet.symbols.iter() {
+            let lib = sheet.lib_symbol(symbol);
+            let (ref_des, unit) = symbol.reference(instance);
+            if !ref_des.starts_with('#') {
+                comps.push(Comp {
+                    ref_des,
+                    instance,
+                    symbol,
+                    lib,
+                });
+            }
+
+            let lib_pins = lib.pins.iter().filter(|pin| {
+                (pin.unit == 0 || pin.unit == unit) && (pin.style == 0 || pin.style == symbol.style)
+            });
+            for lib_pin in lib_pins {
+                let at = symbol.place(lib_pin.at);
+                let pin = PlacedPin {
+                    ref_des,
+                    num: lib_pin.num,
+                    name: lib_pin.name,
+                    typ: lib_pin.typ,
+                    no_connect: sheet.no_connects.contains(&at),
+                };
+                let key = Key::Pin(pins.len());
+                wire_points.push(at);
+                conn.union(Key::Wire(i, at), key.clone());
+
+                if lib_pin.typ == "power_in" && (lib_pin.hidden || lib.power) {
+                    let name = match lib.power {
+                        true => symbol.property("Value").unwrap_or(lib_pin.name),
+                        false => lib_pin.name,
+                    };
+                    conn.union(key.clone(), Key::Global(name.to_owned()));
+                    drivers.push((key, driver(Driver::POWER, name.to_owned())));
+                } else if pin.is_real() {
+                    let driver = Driver {
+                        passive: pin.typ == "passive",
+                        ..driver(Driver::PIN, format!("Net-({})", pin.label()))
+                    };
+                    drivers.push((key, driver));
+                }
+                pins.push(pin);
+            }
+        }
+
+        connect_segments(&mut conn, &sheet.wires, &wire_points, |p| Key::Wire(i, p));
+        connect_segments(&mut conn, &sheet.buses, &bus_points, |p| Key::Bus(i, p));
+    }
+
+    // Bus sheet pins connect the members in the child sheet to the members of the bus in the parent sheet. The parent
+    // members are named by a bus label on the same bus, if there is one.
+    for (c, child) in instances.iter().enumerate() {
+        let Some((p, index)) = child.parent else {
+            continue;
+        };
+        let parent = &sheets[instances[p].file];
+        for pin in parent.sheets[index].pins.iter() {
+            let Some(inner) = bus_members(pin.name) else {
+                continue;
+            };
+            let bus = conn.root(Key::Bus(p, pin.at));
+            let outer = parent
+                .labels
+                .iter()
+                .filter(|label| label.kind != LabelKind::Global)
+                .find_map(|label| {
+                    let members = bus_members(label.text)?;
+                    (conn.root(Key::Bus(p, label.at)) == bus).then_some(members)
+                })
+                .unwrap_or_else(|| inner.clone());
+            for (outer, inner) in outer.into_iter().zip(inner) {
+                conn.union(Key::Local(p, outer), Key::Local(c, inner));
+            }
+        }
+    }
+
+    let mut best: HashMap<usize, &Driver> = HashMap::new();
+    for (key, driver) in drivers.iter() {
+        let root = conn.root(key.clone());
+        let current = best.entry(root).or_insert(driver);
+        if driver.strength(current) == Ordering::Greater {
+            *current = driver;
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<&PlacedPin<'a>>> = HashMap::new();
+    for (index, pin) in pins.iter().enumerate().filter(|(_, pin)| pin.is_real()) {
+        let root = conn.root(Key::Pin(index));
+        groups.entry(root).or_default().push(pin);
+    }
+    let mut nets: Vec<(String, Vec<&PlacedPin<'a>>)> = groups
+        .into_iter()
+        .map(|(root, mut nodes)| {
+            nodes.sort_by(|a, b| {
+                natural_cmp(a.ref_des, b.ref_des).then_with(|| natural_cmp(a.num, b.num))
+            });
+            nodes.dedup_by(|a, b| a.ref_des == b.ref_des && a.num == b.num);
+            let name = match best.get(&root) {
+                Some(driver) if driver.priority > Driver::PIN || nodes.len() > 1 => {
+                    driver.name.clone()
+                }
+                _ => format!("unconnected-({})", nodes[0].label()),
+            };
+            (name, nodes)
+        })
+        .collect();
+    nets.sort_by(|a, b| natural_cmp(&a.0, &b.0));
+
+    comps.sort_by(|a, b| natural_cmp(a.ref_des, b.ref_des));
+    comps.dedup_by(|a, b| a.ref_des == b.ref_des);
+
+    let mut out = String::new();
+    writeln!(out, "(export (version \"E\")").unwrap();
+    write_design(&mut out, files, sheets, instances);
+    write_components(&mut out, files, &comps);
+    write_libparts(&mut out, &comps);
+    writeln!(out, "  (nets").unwrap();
+    for (code, (name, nodes)) in nets.iter().enumerate() {
+        writeln!(
+            out,
+            "    (net (code \"{}\") (name {})",
+            code + 1,
+            quoted(name)
+        )
+        .unwrap();
+        for node in nodes.iter() {
+            write!(
+                out,
+                "      (node (ref {}) (pin {})",
+                quoted(node.ref_des),
+                quoted(node.num)
+            )
+            .unwrap();
+            if let Some(function) = node.function() {
+                write!(out, " (pinfunction {})", quoted(function)).unwrap();
+            }
+            let suffix = if node.no_connect { "+no_connect" } else { "" };
+            writeln!(out, " (pintype \"{}{suffix}\"))", node.typ).unwrap();
+        }
+        writeln!(out, "    )").unwrap();
+    }
+    writeln!(out, "  ))").unwrap();
+    out
+}
+
+fn write_design(out: &mut String, files: &[&str], sheets: &[Sheet<'_>], instances: &[Instance]) {
+    writeln!(out, "  (design").unwrap();
+    writeln!(out, "    (source {})", quoted(files[0])).unwrap();
+    writeln!(out, "    (tool \"kicad_netlist\")").unwrap();
+    for (number, instance) in instances.iter().enumerate() {
+        let block = &sheets[instance.file].title_block;
+        writeln!(
+            out,
+            "    (sheet (number \"{}\") (name {}) (tstamps {})",
+            number + 1,
+            quoted(&instance.names),
+            quoted(&instance.tstamps)
+        )
+        .unwrap();
+        writeln!(out, "      (title_block").unwrap();
+        writeln!(out, "        (title {})", quoted(block.title)).unwrap();
+        writeln!(out, "        (company {})", quoted(block.company)).unwrap();
+        writeln!(out, "        (rev {})", quoted(block.rev)).unwrap();
+        writeln!(out, "        (date {})", quoted(block.date)).unwrap();
+        writeln!(out, "        (source {})))", quoted(files[instance.file])).unwrap();
+    }
+    writeln!(out, "  )").unwrap();
+}
+
+/// Properties which are not user fields
+fn is_field(name: &str) -> bool {
+    !matches!(
+        name,
+        "Reference" | "Value" | "Footprint" | "Datasheet" | "Description"
+    ) && !name.starts_with("ki_")
+}
+
+fn description<'a>(lib: &LibSymbol<'a>) -> &'a str {
+    lib.property("ki_description")
+        .or_else(|| lib.property("Description"))
+        .unwrap_or_default()
+}
+
+fn write_components(out: &mut String, files: &[&str], comps: &[Comp<'_>]) {
+    writeln!(out, "  (components").unwrap();
+    for comp in comps.iter() {
+        let symbol = comp.symbol;
+        let (lib, part) = symbol.lib_id.split_once(':').unwrap_or(("", symbol.lib_id));
+        writeln!(out, "    (comp (ref {})", quoted(comp.ref_des)).unwrap();
+        let value = symbol.property("Value").unwrap_or_default();
+        writeln!(out, "      (value {})", quoted(value)).unwrap();
+        if let Some(footprint) = symbol.property("Footprint").filter(|s| !s.is_empty()) {
+            writeln!(out, "      (footprint {})", quoted(footprint)).unwrap();
+        }
+        if let Some(datasheet) = symbol
+            .property("Datasheet")
+            .filter(|s| !s.is_empty() && *s != "~")
+        {
+            writeln!(out, "      (datasheet {})", quoted(datasheet)).unwrap();
+        }
+
+        let mut fields: Vec<_> = symbol
+            .properties
+            .iter()
+            .filter(|(name, _)| is_field(name))
+            .collect();
+        fields.sort_by(|a, b| natural_cmp(a.0, b.0));
+        if !fields.is_empty() {
+            writeln!(out, "      (fields").unwrap();
+            for (name, value) in fields.iter() {
+                writeln!(
+                    out,
+                    "        (field (name {}) {})",
+                    quoted(name),
+                    quoted(value)
+                )
+                .unwrap();
+            }
+            writeln!(out, "      )").unwrap();
+        }
+        writeln!(
+            out,
+            "      (libsource (lib {}) (part {}) (description {}))",
+            quoted(lib),
+            quoted(part),
+            quoted(description(comp.lib))
+        )
+        .unwrap();
+
+        let mut properties: Vec<(&str, &str)> = fields.iter().map(|&&field| field).collect();
+        if symbol.dnp {
+            properties.push(("dnp", ""));
+        }
+        if !symbol.in_bom {
+            properties.push(("exclude_from_bom", ""));
+        }
+        properties.push(("Sheetname", &comp.instance.sheet_name));
+        properties.push(("Sheetfile", files[comp.instance.file]));
+        for name in ["ki_description", "ki_keywords"] {
+            if let Some(value) = comp.lib.property(name) {
+                properties.push((name, value));
+            }
+        }
+        for (name, value) in properties {
+            writeln!(
+                out,
+                "      (property (name {}) (value {}))",
+                quoted(name),
+                quoted(value)
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            out,
+            "      (sheetpath (names {}) (tstamps {}))",
+            quoted(&comp.instance.names),
+            quoted(&comp.instance.tstamps)
+        )
+        .unwrap();
+        writeln!(out, "      (tstamps {}))", quoted(symbol.uuid)).unwrap();
+    }
+    writeln!(out, "  )").unwrap();
+}
+
+fn write_libparts(out: &mut String, comps: &[Comp<'_>]) {
+    let mut libs: Vec<(&str, &LibSymbol<'_>)> = vec![];
+    for comp in comps.iter() {
+        if !libs.iter().any(|(lib_id, _)| *lib_id == comp.symbol.lib_id) {
+            libs.push((comp.symbol.lib_id, comp.lib));
+        }
+    }
+    libs.sort_by(|a, b| natural_cmp(a.0, b.0));
+
+    writeln!(out, "  (libparts").unwrap();
+    for (lib_id, symbol) in libs {
+        let (lib, part) = lib_id.split_once(':').unwrap_or(("", lib_id));
+        writeln!(
+            out,
+            "    (libpart (lib {}) (part {})",
+            quoted(lib),
+            quoted(part)
+        )
+        .unwrap();
+        writeln!(out, "      (description {})", quoted(description(symbol))).unwrap();
+        if let Some(docs) = symbol.property("Datasheet") {
+            writeln!(out, "      (docs {})", quoted(docs)).unwrap();
+        }
+        let filters = symbol.property("ki_fp_filters").unwrap_or_default();
+        if !filters.is_empty() {
+            writeln!(out, "      (footprints").unwrap();
+            for filter in filters.split_whitespace() {
+                writeln!(out, "        (fp {})", quoted(filter)).unwrap();
+            }
+            writeln!(out, "      )").unwrap();
+        }
+        writeln!(out, "      (fields").unwrap();
+        for name in ["Reference", "Value", "Footprint", "Datasheet"] {
+            if let Some(value) = symbol.property(name).filter(|s| !s.is_empty()) {
+                writeln!(
+                    out,
+                    "        (field (name {}) {})",
+                    quoted(name),
+                    quoted(value)
+                )
+                .unwrap();
+            }
+        }
+        writeln!(out, "      )").unwrap();
+
+        let mut pins: Vec<_> = symbol.pins.iter().collect();
+        pins.sort_by(|a, b| natural_cmp(a.num, b.num));
+        pins.dedup_by(|a, b| a.num == b.num);
+        writeln!(out, "      (pins").unwrap();
+        for pin in pins {
+            let name = if pin.name == "~" { "" } else { pin.name };
+            writeln!(
+                out,
+                "        (pin (num {}) (name {}) (type \"{}\"))",
+                quoted(pin.num),
+                quoted(name),
+                pin.typ
+            )
+            .unwrap();
+        }
+        writeln!(out, "      ))").unwrap();
+    }
+    writeln!(out, "  )").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_expand_bus_members() {
+        assert_eq!(bus_members("D[0..2]").unwrap(), vec!["D0", "D1", "D2"]);
+        assert_eq!(bus_members("A[1..0]").unwrap(), vec!["A1", "A0"]);
+        assert_eq!(
+            bus_members("I2C{SCL SDA}").unwrap(),
+            vec!["I2C.SCL", "I2C.SDA"]
+        );
+        assert_eq!(bus_members("{EN D[0..1]}").unwrap(), vec!["EN", "D0", "D1"]);
+        assert_eq!(bus_members("D0"), None);
+        assert_eq!(bus_members("A[x]"), None);
+    }
+
+    #[test]
+    fn crossing_is_not_on_segment() {
+        let segment = ((0, 0), (10, 0));
+        assert!(on_segment((5, 0), segment));
+        assert!(on_segment((10, 0), segment));
+        assert!(!on_segment((5, 1), segment));
+        assert!(!on_segment((11, 0), segment));
+    }
+}
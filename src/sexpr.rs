@@ -21,11 +21,36 @@ impl<'a> Display for SExpr<'a> {
                 }
                 write!(f, ")")
             }
-            SExpr::String(s) => write!(f, "\"{}\"", s),
+            SExpr::String(s) => write!(f, "{}", quoted(s)),
         }
     }
 }
 
+/// Quote a string for writing, escaping any `"` or `\` which is not already part of an escape sequence
+///
+/// Parsed strings keep their escape sequences, so they are written back unchanged, while text from elsewhere, such
+/// as a file path, is escaped.
+pub(crate) fn quoted(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => match chars.peek() {
+                Some('"' | '\\' | 'b' | 'n' | 'f' | 'r' | 't' | 'u') => {
+                    out.push(c);
+                    out.push(chars.next().unwrap());
+                }
+                _ => out.push_str("\\\\"),
+            },
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
 impl<'a> SExpr<'a> {
     pub fn value(&self, label: &str) -> Result<&'a str, ParseError> {
         let child = self.child(label)?;
@@ -40,6 +65,23 @@ impl<'a> SExpr<'a> {
         Err(ParseError::MissingValue())
     }
 
+    /// The first string among the children
+    pub fn string(&self) -> Option<&'a str> {
+        self.strings().next()
+    }
+
+    /// All strings among the children
+    pub fn strings(&self) -> impl Iterator<Item = &'a str> + '_ {
+        let children: &[SExpr<'a>] = match self {
+            SExpr::SExpr(_, children) => children,
+            SExpr::String(_) => &[],
+        };
+        children.iter().filter_map(|child| match child {
+            SExpr::String(s) => Some(*s),
+            SExpr::SExpr(_, _) => None,
+        })
+    }
+
     pub fn label(&self) -> Option<&'a str> {
         match self {
             SExpr::SExpr(label, _) => Some(label),
@@ -100,19 +142,18 @@ impl<'a> TryFrom<&'a String> for SExpr<'a> {
 mod tests {
     use super::*;
 
-    macro_rules! test_data {
-        ($fname:expr) => {
-            std::fs::read_to_string(concat!(
-                env!("CARGO_MANIFEST_DIR"),
-                "/resources/test/",
-                $fname
-            ))
-            .unwrap()
-        };
-    }
     #[test]
     fn sexpr_can_parse_full_file() {
         let i = &test_data!("kvt.net");
         let _ = SExpr::try_from(i).unwrap();
     }
+
+    #[test]
+    fn strings_are_quoted() {
+        assert_eq!(quoted("GND"), r#""GND""#);
+        assert_eq!(quoted(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quoted(r"C:\kicad"), r#""C:\\kicad""#);
+        // Escape sequences of parsed strings are kept
+        assert_eq!(quoted(r#"say \"hi\"\n"#), r#""say \"hi\"\n""#);
+    }
 }
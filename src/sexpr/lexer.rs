@@ -79,7 +79,7 @@ mod tests {
     #[test]
     fn test() {
         let input = "(a \"b\" \"\" \n)";
-        let mut it = TokenIter {
+        let it = TokenIter {
             iter: LogosTokenKind::lexer(input).spanned(),
         };
         let expected = vec![
@@ -92,7 +92,7 @@ mod tests {
 
         let mut result = vec![];
 
-        while let Some(token) = it.next() {
+        for token in it {
             result.push((token.kind, &input[token.span.clone()]));
         }
 
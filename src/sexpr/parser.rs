@@ -21,7 +21,7 @@ pub enum ParsedSExpr {
 }
 
 impl ParsedSExpr {
-    fn into_sexpr(self, input: &str) -> SExpr {
+    fn into_sexpr(self, input: &str) -> SExpr<'_> {
         match self {
             ParsedSExpr::SExpr(label_span, children) => {
                 let label = &input[label_span];
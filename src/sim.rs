@@ -0,0 +1,564 @@
+//! Event driven gate level simulation
+//!
+//! Components are simulated using behavioural models looked up by [`PartId`] in a [`ModelRegistry`]. Components
+//! without a model, such as connectors and passives, are ignored. Nets can be driven from the outside with
+//! [`Simulator::set`] and pulled weakly with [`Simulator::pull`], and their values read back with [`Simulator::get`].
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{NetList, NetName, PartId, PinType, SimError};
+
+/// A four state logic value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Logic {
+    Low,
+    High,
+    /// Unknown or conflicting value
+    Unknown,
+    /// Not driven
+    HighZ,
+}
+
+impl Logic {
+    /// An undriven input reads as unknown
+    fn input(self) -> Logic {
+        match self {
+            Logic::HighZ => Logic::Unknown,
+            v => v,
+        }
+    }
+}
+
+impl From<bool> for Logic {
+    fn from(value: bool) -> Self {
+        if value {
+            Logic::High
+        } else {
+            Logic::Low
+        }
+    }
+}
+
+impl std::ops::Not for Logic {
+    type Output = Logic;
+
+    fn not(self) -> Self::Output {
+        match self.input() {
+            Logic::Low => Logic::High,
+            Logic::High => Logic::Low,
+            _ => Logic::Unknown,
+        }
+    }
+}
+
+impl std::ops::BitAnd for Logic {
+    type Output = Logic;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        match (self.input(), rhs.input()) {
+            (Logic::Low, _) | (_, Logic::Low) => Logic::Low,
+            (Logic::High, Logic::High) => Logic::High,
+            _ => Logic::Unknown,
+        }
+    }
+}
+
+impl std::ops::BitOr for Logic {
+    type Output = Logic;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        match (self.input(), rhs.input()) {
+            (Logic::High, _) | (_, Logic::High) => Logic::High,
+            (Logic::Low, Logic::Low) => Logic::Low,
+            _ => Logic::Unknown,
+        }
+    }
+}
+
+impl std::ops::BitXor for Logic {
+    type Output = Logic;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        match (self.input(), rhs.input()) {
+            (Logic::Low, v) | (v, Logic::Low) => v,
+            (Logic::High, v) | (v, Logic::High) => !v,
+            _ => Logic::Unknown,
+        }
+    }
+}
+
+type EvalFn = dyn Fn(&[Logic]) -> Vec<Logic>;
+
+/// Behavioural model of a part
+///
+/// The model maps the values of the input pins, given by pin name, to the values of the output pins. How an output
+/// drives its net depends on the pin type: an open collector output only drives low, an open emitter output only
+/// drives high, and any output can return [`Logic::HighZ`] to release the net.
+pub struct Model {
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    eval: Box<EvalFn>,
+}
+
+impl std::fmt::Debug for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Model")
+            .field("inputs", &self.inputs)
+            .field("outputs", &self.outputs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Model {
+    pub fn new(
+        inputs: &[&str],
+        outputs: &[&str],
+        eval: impl Fn(&[Logic]) -> Vec<Logic> + 'static,
+    ) -> Self {
+        Self {
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            outputs: outputs.iter().map(|s| s.to_string()).collect(),
+            eval: Box::new(eval),
+        }
+    }
+
+    fn gate(inputs: &[&str], output: &str, op: fn(Logic, Logic) -> Logic, invert: bool) -> Self {
+        Self::new(inputs, &[output], move |values| {
+            let v = values.iter().copied().reduce(op).unwrap_or(Logic::Unknown);
+            vec![if invert { !v } else { v }]
+        })
+    }
+
+    pub fn and(inputs: &[&str], output: &str) -> Self {
+        Self::gate(inputs, output, |a, b| a & b, false)
+    }
+
+    pub fn nand(inputs: &[&str], output: &str) -> Self {
+        Self::gate(inputs, output, |a, b| a & b, true)
+    }
+
+    pub fn or(inputs: &[&str], output: &str) -> Self {
+        Self::gate(inputs, output, |a, b| a | b, false)
+    }
+
+    pub fn nor(inputs: &[&str], output: &str) -> Self {
+        Self::gate(inputs, output, |a, b| a | b, true)
+    }
+
+    pub fn xor(inputs: &[&str], output: &str) -> Self {
+        Self::gate(inputs, output, |a, b| a ^ b, false)
+    }
+
+    pub fn buffer(input: &str, output: &str) -> Self {
+        Self::new(&[input], &[output], |values| vec![values[0].input()])
+    }
+
+    pub fn inverter(input: &str, output: &str) -> Self {
+        Self::new(&[input], &[output], |values| vec![!values[0]])
+    }
+}
+
+/// Behavioural models keyed by part
+#[derive(Debug, Default)]
+pub struct ModelRegistry {
+    models: HashMap<(String, String), Model>,
+}
+
+impl ModelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, part_id: PartId<'_>, model: Model) {
+        self.models
+            .insert((part_id.lib.to_owned(), part_id.part.to_owned()), model);
+    }
+
+    pub fn find(&self, part_id: PartId<'_>) -> Option<&Model> {
+        self.models
+            .get(&(part_id.lib.to_owned(), part_id.part.to_owned()))
+    }
+}
+
+/// How an output pin drives its net
+#[derive(Debug, Clone, Copy)]
+enum Drive {
+    PushPull,
+    OpenCollector,
+    OpenEmitter,
+}
+
+impl Drive {
+    fn apply(self, value: Logic) -> Logic {
+        match (self, value) {
+            (Drive::OpenCollector, Logic::High) | (Drive::OpenEmitter, Logic::Low) => Logic::HighZ,
+            (_, v) => v,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Instance<'m> {
+    model: &'m Model,
+    inputs: Vec<usize>,
+    outputs: Vec<(usize, Drive)>,
+    values: Vec<Logic>,
+}
+
+/// Resolve the value of a net from the values driven onto it
+fn resolve(drivers: impl IntoIterator<Item = Logic>) -> Logic {
+    drivers
+        .into_iter()
+        .fold(Logic::HighZ, |acc, v| match (acc, v) {
+            (Logic::HighZ, v) | (v, Logic::HighZ) => v,
+            (a, b) if a == b => a,
+            _ => Logic::Unknown,
+        })
+}
+
+/// Whether both values are driven onto a net at once
+fn is_conflict(drivers: impl IntoIterator<Item = Logic>) -> bool {
+    let (mut low, mut high) = (false, false);
+    for v in drivers {
+        low |= v == Logic::Low;
+        high |= v == Logic::High;
+    }
+    low && high
+}
+
+/// A simulation of a netlist
+#[derive(Debug)]
+pub struct Simulator<'a, 'm> {
+    names: Vec<NetName<'a>>,
+    instances: Vec<Instance<'m>>,
+    fanout: Vec<Vec<usize>>,
+    drivers: Vec<Vec<(usize, usize)>>,
+    stimulus: Vec<Option<Logic>>,
+    pulls: Vec<Option<Logic>>,
+    values: Vec<Logic>,
+    /// Whether each net is driven high and low at the same time
+    conflicted: Vec<bool>,
+    queue: VecDeque<usize>,
+    limit: usize,
+}
+
+impl<'a, 'm> Simulator<'a, 'm> {
+    pub fn new(netlist: &NetList<'a>, registry: &'m ModelRegistry) -> Result<Self, SimError> {
+        let names: Vec<NetName<'a>> = netlist.nets.iter().map(|net| net.name).collect();
+        let index: HashMap<NetName<'a>, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect();
+
+        let mut instances = vec![];
+        let mut fanout = vec![vec![]; names.len()];
+        let mut drivers = vec![vec![]; names.len()];
+
+        for comp in netlist.components.iter() {
+            let Some(model) = registry.find(comp.part_id) else {
+                continue;
+            };
+
+            let pin = |name: &String| {
+                comp.pins
+                    .iter()
+                    .find(|pin| pin.name.as_str() == name)
+                    .ok_or(SimError::UnknownPin(comp.ref_des.to_string(), name.clone()))
+            };
+
+            let net = |name: NetName<'_>| {
+                index
+                    .get(&name)
+                    .copied()
+                    .ok_or(SimError::UnknownNet(name.to_string()))
+            };
+
+            let inputs = model
+                .inputs
+                .iter()
+                .map(|name| net(pin(name)?.net))
+                .collect::<Result<Vec<_>, SimError>>()?;
+
+            let outputs = model
+                .outputs
+                .iter()
+                .map(|name| {
+                    let pin = pin(name)?;
+                    let drive = match pin.typ {
+                        PinType::OpenCollector => Drive::OpenCollector,
+                        PinType::OpenEmitter => Drive::OpenEmitter,
+                        _ => Drive::PushPull,
+                    };
+                    Ok((net(pin.net)?, drive))
+                })
+                .collect::<Result<Vec<_>, SimError>>()?;
+
+            let n = instances.len();
+            for &net in inputs.iter() {
+                fanout[net].push(n);
+            }
+            for (i, &(net, _)) in outputs.iter().enumerate() {
+                drivers[net].push((n, i));
+            }
+
+            instances.push(Instance {
+                model,
+                inputs,
+                values: vec![Logic::Unknown; outputs.len()],
+                outputs,
+            });
+        }
+
+        let limit = 1000 * (instances.len() + 1);
+
+        let mut sim = Self {
+            stimulus: vec![None; names.len()],
+            pulls: vec![None; names.len()],
+            values: vec![Logic::HighZ; names.len()],
+            conflicted: vec![false; names.len()],
+            queue: (0..instances.len()).collect(),
+            names,
+            instances,
+            fanout,
+            drivers,
+            limit,
+        };
+        // Driven nets start out as unknown, like the outputs driving them, rather than floating
+        for net in 0..sim.names.len() {
+            if !sim.drivers[net].is_empty() {
+                sim.update(net);
+            }
+        }
+        Ok(sim)
+    }
+
+    fn net(&self, name: NetName<'_>) -> Result<usize, SimError> {
+        self.names
+            .iter()
+            .position(|n| n.as_str() == name.as_str())
+            .ok_or(SimError::UnknownNet(name.to_string()))
+    }
+
+    /// Drive a net from outside the circuit
+    ///
+    /// Passing [`Logic::HighZ`] releases the net.
+    pub fn set(&mut self, name: NetName<'_>, value: Logic) -> Result<(), SimError> {
+        let net = self.net(name)?;
+        self.stimulus[net] = Some(value).filter(|v| *v != Logic::HighZ);
+        self.update(net);
+        Ok(())
+    }
+
+    /// Pull a net weakly to a value, as a pull-up or pull-down resistor would
+    pub fn pull(&mut self, name: NetName<'_>, value: Logic) -> Result<(), SimError> {
+        let net = self.net(name)?;
+        self.pulls[net] = Some(value).filter(|v| *v != Logic::HighZ);
+        self.update(net);
+        Ok(())
+    }
+
+    /// The current value of a net
+    pub fn get(&self, name: NetName<'_>) -> Result<Logic, SimError> {
+        Ok(self.values[self.net(name)?])
+    }
+
+    /// Propagate all pending changes until the circuit is stable
+    pub fn settle(&mut self) -> Result<(), SimError> {
+        let mut evaluations = 0;
+        while let Some(n) = self.queue.pop_front() {
+            evaluations += 1;
+            if evaluations > self.limit {
+                self.queue.clear();
+                return Err(SimError::Oscillation(self.limit));
+            }
+
+            let instance = &self.instances[n];
+            let inputs: Vec<Logic> = instance
+                .inputs
+                .iter()
+                .map(|&net| self.values[net])
+                .collect();
+            let mut values = (instance.model.eval)(&inputs);
+            values.resize(instance.outputs.len(), Logic::Unknown);
+
+            let instance = &mut self.instances[n];
+            if values == instance.values {
+                continue;
+            }
+            instance.values = values;
+
+            let nets: Vec<usize> = instance.outputs.iter().map(|(net, _)| *net).collect();
+            for net in nets {
+                self.update(net);
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-resolve the value of a net, and schedule the instances reading it if it changed
+    fn update(&mut self, net: usize) {
+        let driven: Vec<Logic> = self.drivers[net]
+            .iter()
+            .map(|&(n, i)| {
+                let instance = &self.instances[n];
+                instance.outputs[i].1.apply(instance.values[i])
+            })
+            .chain(self.stimulus[net])
+            .collect();
+        self.conflicted[net] = is_conflict(driven.iter().copied());
+        let mut value = resolve(driven);
+        if value == Logic::HighZ {
+            value = self.pulls[net].unwrap_or(Logic::HighZ);
+        }
+
+        if value != self.values[net] {
+            self.values[net] = value;
+            for &n in self.fanout[net].iter() {
+                if !self.queue.contains(&n) {
+                    self.queue.push_back(n);
+                }
+            }
+        }
+    }
+
+    /// The nets driven by more than one conflicting value
+    pub fn conflicts(&self) -> Vec<NetName<'a>> {
+        self.conflicted
+            .iter()
+            .enumerate()
+            .filter(|(_, conflicted)| **conflicted)
+            .map(|(net, _)| self.names[net])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::NetListBuilder;
+
+    fn registry() -> ModelRegistry {
+        let mut registry = ModelRegistry::new();
+        registry.register(
+            PartId {
+                lib: "74xGxx",
+                part: "74LVC1G00",
+            },
+            Model::nand(&["A", "B"], "Out"),
+        );
+        registry
+    }
+
+    #[test]
+    fn can_simulate_netlist() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let registry = registry();
+        let mut sim = Simulator::new(&netlist, &registry).unwrap();
+
+        sim.pull("Net-(U2-B)".into(), Logic::High).unwrap();
+
+        sim.set("/A".into(), Logic::High).unwrap();
+        sim.set("/B".into(), Logic::High).unwrap();
+        sim.settle().unwrap();
+        assert_eq!(sim.get("Net-(U1-Out)".into()).unwrap(), Logic::Low);
+        assert_eq!(sim.get("/OUT".into()).unwrap(), Logic::High);
+
+        sim.set("/A".into(), Logic::Low).unwrap();
+        sim.settle().unwrap();
+        assert_eq!(sim.get("Net-(U1-Out)".into()).unwrap(), Logic::High);
+        assert_eq!(sim.get("/OUT".into()).unwrap(), Logic::Low);
+
+        sim.set("/A".into(), Logic::HighZ).unwrap();
+        sim.settle().unwrap();
+        assert_eq!(sim.get("/OUT".into()).unwrap(), Logic::Unknown);
+        // Unknown only because an input is undriven
+        assert_eq!(sim.conflicts(), vec![]);
+    }
+
+    #[test]
+    fn outputs_of_gates_with_floating_inputs_are_unknown() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let registry = registry();
+        let mut sim = Simulator::new(&netlist, &registry).unwrap();
+
+        sim.pull("/OUT".into(), Logic::Low).unwrap();
+        sim.settle().unwrap();
+        assert_eq!(sim.get("/A".into()).unwrap(), Logic::HighZ);
+        assert_eq!(sim.get("Net-(U1-Out)".into()).unwrap(), Logic::Unknown);
+        assert_eq!(sim.get("/OUT".into()).unwrap(), Logic::Unknown);
+    }
+
+    #[test]
+    fn finds_conflicting_drivers() {
+        let gate = PartId {
+            lib: "74xGxx",
+            part: "74LVC1G00",
+        };
+        let mut builder = NetListBuilder::new();
+        builder.part(
+            gate,
+            "Single NAND Gate",
+            &[
+                ("1", "A", PinType::Input),
+                ("2", "B", PinType::Input),
+                ("4", "Out", PinType::Output),
+            ],
+        );
+        for (code, net) in [
+            ("1", "/A"),
+            ("2", "/B"),
+            ("3", "/C"),
+            ("4", "/D"),
+            ("5", "/Y"),
+        ] {
+            builder.net(code, net);
+        }
+        for (ref_des, a, b) in [("U1", "/A", "/B"), ("U2", "/C", "/D")] {
+            builder
+                .component(ref_des, "74LVC1G00", gate)
+                .connect(ref_des, "1", a)
+                .connect(ref_des, "2", b)
+                .connect(ref_des, "4", "/Y");
+        }
+        let netlist = builder.build().unwrap();
+        let registry = registry();
+        let mut sim = Simulator::new(&netlist, &registry).unwrap();
+
+        sim.set("/A".into(), Logic::High).unwrap();
+        sim.set("/B".into(), Logic::High).unwrap();
+        sim.settle().unwrap();
+        assert_eq!(sim.conflicts(), vec![]);
+
+        sim.set("/C".into(), Logic::Low).unwrap();
+        sim.settle().unwrap();
+        assert_eq!(sim.get("/Y".into()).unwrap(), Logic::Unknown);
+        assert_eq!(sim.conflicts(), vec!["/Y".into()]);
+    }
+
+    #[test]
+    fn missing_net_is_an_error() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        netlist.nets.retain(|net| net.name.as_str() != "/A");
+        match Simulator::new(&netlist, &registry()) {
+            Err(SimError::UnknownNet(net)) => assert_eq!(net, "/A"),
+            result => panic!("Expected an error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn resolve_works() {
+        use Logic::*;
+        assert_eq!(resolve([]), HighZ);
+        assert_eq!(resolve([HighZ, Low, HighZ]), Low);
+        assert_eq!(resolve([High, High]), High);
+        assert_eq!(resolve([High, Low]), Unknown);
+        // Wired-AND of open collector outputs
+        let oc = |v| Drive::OpenCollector.apply(v);
+        assert_eq!(resolve([oc(High), oc(Low)]), Low);
+        assert_eq!(resolve([oc(High), oc(High)]), HighZ);
+    }
+}
@@ -0,0 +1,81 @@
+//! Helpers for deriving test fixtures from the example netlists
+
+use crate::{Component, NetList, NetNode};
+
+/// The component with the given reference designator
+pub(crate) fn component_mut<'n, 'a>(
+    netlist: &'n mut NetList<'a>,
+    ref_des: &str,
+) -> &'n mut Component<'a> {
+    netlist
+        .components
+        .iter_mut()
+        .find(|comp| comp.ref_des == ref_des.into())
+        .unwrap()
+}
+
+/// The node connecting the given pin, on whichever net it is
+pub(crate) fn node_mut<'n, 'a>(
+    netlist: &'n mut NetList<'a>,
+    ref_des: &str,
+    num: &str,
+) -> &'n mut NetNode<'a> {
+    netlist
+        .nets
+        .iter_mut()
+        .flat_map(|net| net.nodes.iter_mut())
+        .find(|node| node.ref_des == ref_des.into() && node.num == num.into())
+        .unwrap()
+}
+
+/// Move a node from one net to another
+pub(crate) fn move_node<'a>(netlist: &mut NetList<'a>, ref_des: &str, num: &str, to: &'a str) {
+    let node = netlist
+        .nets
+        .iter_mut()
+        .find_map(|net| {
+            let i = net
+                .nodes
+                .iter()
+                .position(|node| node.ref_des == ref_des.into() && node.num == num.into())?;
+            Some(net.nodes.remove(i))
+        })
+        .unwrap();
+    let net = netlist.nets.iter_mut().find(|net| net.name == to.into());
+    net.unwrap().nodes.push(node);
+    let comp = component_mut(netlist, ref_des);
+    if let Some(pin) = comp.pins.iter_mut().find(|pin| pin.num == num.into()) {
+        pin.net = to.into();
+    }
+}
+
+/// Rename a net, along with the pins connected to it
+pub(crate) fn rename_net<'a>(netlist: &mut NetList<'a>, from: &str, to: &'a str) {
+    let nets = netlist.nets.iter_mut();
+    for net in nets.filter(|net| net.name == from.into()) {
+        net.name = to.into();
+    }
+    let pins = netlist
+        .components
+        .iter_mut()
+        .flat_map(|comp| &mut comp.pins);
+    for pin in pins.filter(|pin| pin.net == from.into()) {
+        pin.net = to.into();
+    }
+}
+
+/// Rename a component, along with its nodes and its entry in the part
+pub(crate) fn rename_component<'a>(netlist: &mut NetList<'a>, from: &str, to: &'a str) {
+    component_mut(netlist, from).ref_des = to.into();
+    let parts = netlist
+        .parts
+        .iter_mut()
+        .flat_map(|part| &mut part.components);
+    for ref_des in parts.filter(|ref_des| **ref_des == from.into()) {
+        *ref_des = to.into();
+    }
+    let nodes = netlist.nets.iter_mut().flat_map(|net| &mut net.nodes);
+    for node in nodes.filter(|node| node.ref_des == from.into()) {
+        node.ref_des = to.into();
+    }
+}
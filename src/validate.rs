@@ -0,0 +1,507 @@
+//! Consistency checks for a netlist
+//!
+//! Each node of a net repeats the name and type of the pin it connects to. The parser only makes sure that every pin
+//! of a component is connected to some net, so this module checks the rest: that the nodes agree with the pins of the
+//! libpart and that they refer to components and pins which exist. Pins which use an alternate function only agree
+//! with the libpart when the alternates are known, which [`check_with_symbols`] takes from the library symbols.
+//!
+//! The footprints assigned to the components are checked separately by [`check_footprints`].
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use crate::library::SymbolDef;
+use crate::{Footprint, NetList, NetName, PartId, PinFunction, PinName, PinNum, PinType, RefDes};
+
+/// A single inconsistency in a netlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue<'a> {
+    /// Several components use the same reference designator
+    DuplicateRefDes(RefDes<'a>),
+    /// A node refers to a component which does not exist
+    UnknownComponent {
+        net: NetName<'a>,
+        ref_des: RefDes<'a>,
+        num: PinNum<'a>,
+    },
+    /// A node refers to a pin which the component does not have
+    UnknownPin {
+        net: NetName<'a>,
+        ref_des: RefDes<'a>,
+        num: PinNum<'a>,
+    },
+    /// The pin function of a node differs from the name of the libpart pin
+    FunctionMismatch {
+        ref_des: RefDes<'a>,
+        num: PinNum<'a>,
+        node: PinFunction<'a>,
+        part: PinName<'a>,
+    },
+    /// The pin type of a node differs from the type of the libpart pin
+    TypeMismatch {
+        ref_des: RefDes<'a>,
+        num: PinNum<'a>,
+        node: PinType,
+        part: PinType,
+    },
+    /// A net lists the same pin more than once
+    DuplicateNode {
+        net: NetName<'a>,
+        ref_des: RefDes<'a>,
+        num: PinNum<'a>,
+    },
+    /// A pin is connected to more than one net
+    PinInSeveralNets {
+        ref_des: RefDes<'a>,
+        num: PinNum<'a>,
+        nets: Vec<NetName<'a>>,
+    },
+    /// A component has no footprint
+    MissingFootprint(RefDes<'a>),
+    /// The footprint of a component does not match any of the footprint filters of its part
+    FootprintMismatch {
+        ref_des: RefDes<'a>,
+        footprint: Footprint<'a>,
+        filters: Vec<&'a str>,
+    },
+}
+
+impl Display for Issue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::DuplicateRefDes(ref_des) => {
+                write!(f, "Reference designator {ref_des} is used more than once")
+            }
+            Issue::UnknownComponent { net, ref_des, num } => {
+                write!(f, "Net {net} connects to {ref_des}.{num}, but there is no component {ref_des}")
+            }
+            Issue::UnknownPin { net, ref_des, num } => {
+                write!(f, "Net {net} connects to {ref_des}.{num}, but {ref_des} has no pin {num}")
+            }
+            Issue::FunctionMismatch {
+                ref_des,
+                num,
+                node,
+                part,
+            } => write!(
+                f,
+                "Pin {ref_des}.{num} has function \"{node}\" in the net but is named \"{part}\" in the part"
+            ),
+            Issue::TypeMismatch {
+                ref_des,
+                num,
+                node,
+                part,
+            } => write!(
+                f,
+                "Pin {ref_des}.{num} has type {node} in the net but type {part} in the part"
+            ),
+            Issue::DuplicateNode { net, ref_des, num } => {
+                write!(f, "Net {net} connects to {ref_des}.{num} more than once")
+            }
+            Issue::PinInSeveralNets { ref_des, num, nets } => {
+                let nets: Vec<&str> = nets.iter().map(|net| net.as_str()).collect();
+                write!(
+                    f,
+                    "Pin {ref_des}.{num} is connected to several nets: {}",
+                    nets.join(", ")
+                )
+            }
+            Issue::MissingFootprint(ref_des) => write!(f, "Component {ref_des} has no footprint"),
+            Issue::FootprintMismatch {
+                ref_des,
+                footprint,
+                filters,
+            } => write!(
+                f,
+                "Footprint {footprint} of {ref_des} does not match any of {}",
+                filters.join(", ")
+            ),
+        }
+    }
+}
+
+/// Check that the nets and components of the netlist agree with each other
+///
+/// A node marked as `no_connect` is not compared with the type of the libpart pin, since KiCad sets that type for
+/// any pin with a no connect flag.
+pub fn check<'a>(netlist: &NetList<'a>) -> Vec<Issue<'a>> {
+    check_with_symbols(netlist, |_| None)
+}
+
+/// Check the netlist like [`check`], also accepting the alternate functions of the pins of the library symbols
+///
+/// `find_symbol` looks up the symbol of a part, and may return `None` for parts without alternates.
+pub fn check_with_symbols<'a, 's>(
+    netlist: &NetList<'a>,
+    find_symbol: impl Fn(PartId<'a>) -> Option<&'s SymbolDef<'s>>,
+) -> Vec<Issue<'a>> {
+    let mut issues = vec![];
+
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    for comp in netlist.components.iter() {
+        if !seen.insert(comp.ref_des) && reported.insert(comp.ref_des) {
+            issues.push(Issue::DuplicateRefDes(comp.ref_des));
+        }
+    }
+
+    let mut pin_nets: Vec<((RefDes, PinNum), Vec<NetName>)> = vec![];
+    let mut pin_index: HashMap<(RefDes, PinNum), usize> = HashMap::new();
+
+    for net in netlist.nets.iter() {
+        for node in net.nodes.iter() {
+            let key = (node.ref_des, node.num);
+            let index = *pin_index.entry(key).or_insert_with(|| {
+                pin_nets.push((key, vec![]));
+                pin_nets.len() - 1
+            });
+            if pin_nets[index].1.contains(&net.name) {
+                issues.push(Issue::DuplicateNode {
+                    net: net.name,
+                    ref_des: node.ref_des,
+                    num: node.num,
+                });
+                continue;
+            }
+            pin_nets[index].1.push(net.name);
+
+            let Some(comp) = netlist.find_component(node.ref_des) else {
+                issues.push(Issue::UnknownComponent {
+                    net: net.name,
+                    ref_des: node.ref_des,
+                    num: node.num,
+                });
+                continue;
+            };
+            let Some(pin) = comp.find_pin(node.num) else {
+                issues.push(Issue::UnknownPin {
+                    net: net.name,
+                    ref_des: node.ref_des,
+                    num: node.num,
+                });
+                continue;
+            };
+
+            let alternate = find_symbol(comp.part_id)
+                .and_then(|symbol| symbol.find_pin(node.num))
+                .and_then(|lib_pin| {
+                    lib_pin
+                        .alternates
+                        .iter()
+                        .find(|alt| Some(alt.name.as_str()) == node.function.map(|f| f.as_str()))
+                });
+            if let Some(alternate) = alternate {
+                if node.typ != alternate.typ && node.typ != PinType::Unconnected {
+                    issues.push(Issue::TypeMismatch {
+                        ref_des: node.ref_des,
+                        num: node.num,
+                        node: node.typ,
+                        part: alternate.typ,
+                    });
+                }
+                continue;
+            }
+
+            if let Some(function) = node.function {
+                if function.as_str() != pin.name.as_str() {
+                    issues.push(Issue::FunctionMismatch {
+                        ref_des: node.ref_des,
+                        num: node.num,
+                        node: function,
+                        part: pin.name,
+                    });
+                }
+            }
+            if node.typ != pin.typ && node.typ != PinType::Unconnected {
+                issues.push(Issue::TypeMismatch {
+                    ref_des: node.ref_des,
+                    num: node.num,
+                    node: node.typ,
+                    part: pin.typ,
+                });
+            }
+        }
+    }
+
+    for ((ref_des, num), nets) in pin_nets {
+        if nets.len() > 1 {
+            issues.push(Issue::PinInSeveralNets { ref_des, num, nets });
+        }
+    }
+
+    issues
+}
+
+/// Check the footprint of each component against the footprint filters of its part
+///
+/// A part without any filters accepts every footprint.
+pub fn check_footprints<'a>(netlist: &NetList<'a>) -> Vec<Issue<'a>> {
+    let mut issues = vec![];
+
+    for comp in netlist.components.iter() {
+        let Some(footprint) = comp.footprint else {
+            issues.push(Issue::MissingFootprint(comp.ref_des));
+            continue;
+        };
+        let Some(part) = netlist.find_part(comp.part_id) else {
+            continue;
+        };
+        if part.footprint_filters.is_empty() {
+            continue;
+        }
+        if !part
+            .footprint_filters
+            .iter()
+            .any(|filter| footprint_matches(filter, footprint))
+        {
+            issues.push(Issue::FootprintMismatch {
+                ref_des: comp.ref_des,
+                footprint,
+                filters: part.footprint_filters.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Match a footprint against a footprint filter in the same way as KiCad
+///
+/// The match is case insensitive, `*` matches any number of characters and `?` matches a single character. The
+/// library nickname is only part of the match if the filter contains a `:`.
+pub fn footprint_matches(filter: &str, footprint: Footprint<'_>) -> bool {
+    let name = if filter.contains(':') {
+        footprint.as_str()
+    } else {
+        footprint.name()
+    };
+    let filter: Vec<char> = filter.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    wildcard_match(&filter, &name)
+}
+
+fn wildcard_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::SymbolLib;
+    use crate::test_util::{component_mut, node_mut};
+    use crate::NetNode;
+    use rstest::rstest;
+
+    #[test]
+    fn kvt_is_consistent() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        assert_eq!(check(&netlist), vec![]);
+    }
+
+    #[test]
+    fn finds_bad_nodes() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        // Also connect U1.1 to /B, along with a pin and a component which don't exist
+        let u1_a = node_mut(&mut netlist, "U1", "1").clone();
+        let unknown = [("U1", "9"), ("U9", "1")].map(|(ref_des, num)| NetNode {
+            ref_des: ref_des.into(),
+            num: num.into(),
+            function: None,
+            typ: PinType::Input,
+        });
+        let b = netlist.nets.iter_mut().find(|net| net.name == "/B".into());
+        b.unwrap().nodes.extend([u1_a].into_iter().chain(unknown));
+        node_mut(&mut netlist, "U2", "1").function = Some("C".into());
+        node_mut(&mut netlist, "U2", "4").typ = PinType::TriState;
+
+        assert_eq!(
+            check(&netlist),
+            vec![
+                Issue::UnknownPin {
+                    net: "/B".into(),
+                    ref_des: "U1".into(),
+                    num: "9".into()
+                },
+                Issue::UnknownComponent {
+                    net: "/B".into(),
+                    ref_des: "U9".into(),
+                    num: "1".into()
+                },
+                Issue::TypeMismatch {
+                    ref_des: "U2".into(),
+                    num: "4".into(),
+                    node: PinType::TriState,
+                    part: PinType::Output
+                },
+                Issue::FunctionMismatch {
+                    ref_des: "U2".into(),
+                    num: "1".into(),
+                    node: "C".into(),
+                    part: "A".into()
+                },
+                Issue::PinInSeveralNets {
+                    ref_des: "U1".into(),
+                    num: "1".into(),
+                    nets: vec!["/A".into(), "/B".into()]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_duplicate_nodes() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        let node = netlist.nets[0].nodes[0].clone();
+        netlist.nets[0].nodes.push(node);
+
+        let issues = check(&netlist);
+        assert_eq!(
+            issues,
+            vec![Issue::DuplicateNode {
+                net: "/A".into(),
+                ref_des: "J1".into(),
+                num: "2".into()
+            }]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "Net /A connects to J1.2 more than once"
+        );
+    }
+
+    #[test]
+    fn accepts_alternate_functions() {
+        const LIB: &str = r#"(kicad_symbol_lib (version 20231120) (generator "kicad_symbol_editor")
+  (symbol "74LVC1G00"
+    (symbol "74LVC1G00_1_1"
+      (pin input line (at -7.62 2.54 0) (length 7.62)
+        (name "A" (effects)) (number "1" (effects))
+        (alternate "CLK" input clock)
+        (alternate "EN" output line)))))"#;
+        let lib = SymbolLib::parse(LIB).unwrap();
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        let node = node_mut(&mut netlist, "U1", "1");
+        node.function = Some("EN".into());
+        node.typ = PinType::Output;
+
+        let find_symbol = |part_id: PartId<'_>| lib.find(part_id.part);
+        assert_eq!(check_with_symbols(&netlist, find_symbol), vec![]);
+        assert_eq!(check(&netlist).len(), 2);
+
+        netlist.nets[0].nodes[1].typ = PinType::Input;
+        assert_eq!(
+            check_with_symbols(&netlist, find_symbol),
+            vec![Issue::TypeMismatch {
+                ref_des: "U1".into(),
+                num: "1".into(),
+                node: PinType::Input,
+                part: PinType::Output
+            }]
+        );
+    }
+
+    #[test]
+    fn no_connect_nodes_are_not_type_mismatches() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        node_mut(&mut netlist, "U1", "1").typ = PinType::Unconnected;
+        assert_eq!(check(&netlist), vec![]);
+    }
+
+    #[test]
+    fn finds_duplicate_ref_des() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        let duplicate = netlist.components[1].clone();
+        netlist.components.push(duplicate.clone());
+        netlist.components.push(duplicate);
+
+        let issues = check(&netlist);
+        assert_eq!(issues, vec![Issue::DuplicateRefDes("R1".into())]);
+        assert_eq!(
+            issues[0].to_string(),
+            "Reference designator R1 is used more than once"
+        );
+    }
+
+    #[rstest]
+    #[case("SOT?23*", "Package_TO_SOT_SMD:SOT-23-5_HandSoldering", true)]
+    #[case("sot?23*", "Package_TO_SOT_SMD:SOT-23-5", true)]
+    #[case("SOT?23*", "Resistor_SMD:R_0603_1608Metric", false)]
+    #[case(
+        "Connector*:*_1x??_*",
+        "Connector_PinHeader_2.54mm:PinHeader_1x06_P2.54mm_Vertical",
+        true
+    )]
+    #[case(
+        "Connector*:*_1x??_*",
+        "Connector_PinHeader_2.54mm:PinHeader_2x06_P2.54mm_Vertical",
+        false
+    )]
+    #[case("Connector*:*_1x??_*", "PinHeader_1x06_P2.54mm_Vertical", false)]
+    #[case("R_*", "Resistor_SMD:R_0603_1608Metric", true)]
+    #[case("R_*", "Resistor_SMD:C_0603_1608Metric", false)]
+    #[case("*0603*", "Resistor_SMD:R_0603_1608Metric", true)]
+    fn can_match_footprint_filters(
+        #[case] filter: &str,
+        #[case] footprint: &str,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(footprint_matches(filter, footprint.into()), expected);
+    }
+
+    #[test]
+    fn finds_footprint_issues() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        // Only the footprint of U1 is changed, not the one of U2
+        component_mut(&mut netlist, "U1").footprint = Some("Resistor_SMD:R_0603_1608Metric".into());
+        let issues = check_footprints(&netlist);
+
+        assert_eq!(
+            issues,
+            vec![
+                Issue::MissingFootprint("R1".into()),
+                Issue::FootprintMismatch {
+                    ref_des: "U1".into(),
+                    footprint: "Resistor_SMD:R_0603_1608Metric".into(),
+                    filters: vec![
+                        "SOT?23*",
+                        "Texas?R-PDSO-G5?DCK*",
+                        "Texas?R-PDSO-N5?DRL*",
+                        "Texas?X2SON*0.8x0.8mm*P0.48mm*"
+                    ]
+                },
+            ]
+        );
+    }
+}
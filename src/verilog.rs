@@ -0,0 +1,475 @@
+//! Structural Verilog export
+//!
+//! The module is generated from the `Verilog*` fields of the components:
+//!
+//! - `VerilogCode` is a template for the component. Every `_(A,B,Out)` in it is replaced by the reference designator
+//!   of the component followed by the nets connected to the pins named `A`, `B` and `Out`.
+//! - `VerilogModulePort` is a comma separated list of pin numbers. The nets connected to these pins become ports of
+//!   the module, and all other nets become wires.
+//! - `VerilogInclude` names a file which is pulled in with an `` `include `` line.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::{Component, Net, NetList, NetName, PinNum, PinType, RefDes, VerilogError};
+
+const CODE_FIELD: &str = "VerilogCode";
+const PORT_FIELD: &str = "VerilogModulePort";
+const INCLUDE_FIELD: &str = "VerilogInclude";
+
+/// Direction of a module port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortDirection {
+    Input,
+    Output,
+    InOut,
+}
+
+impl std::fmt::Display for PortDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortDirection::Input => write!(f, "input"),
+            PortDirection::Output => write!(f, "output"),
+            PortDirection::InOut => write!(f, "inout"),
+        }
+    }
+}
+
+/// Generate a Verilog module with the given name from the netlist
+pub fn to_verilog(netlist: &NetList<'_>, module_name: &str) -> Result<String, VerilogError> {
+    let idents = net_identifiers(netlist, module_name);
+
+    let mut includes: Vec<&str> = vec![];
+    for comp in netlist.components.iter() {
+        if let Some(include) = comp.find_property(INCLUDE_FIELD) {
+            if !includes.contains(&include) {
+                includes.push(include);
+            }
+        }
+    }
+
+    let mut ports: Vec<(NetName, PortDirection)> = vec![];
+    for comp in netlist.components.iter() {
+        let Some(port_pins) = comp.find_property(PORT_FIELD) else {
+            continue;
+        };
+        for num in port_pins
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            let pin = comp
+                .find_pin(PinNum::from(num))
+                .ok_or(VerilogError::UnknownPin(
+                    comp.ref_des.to_string(),
+                    num.to_owned(),
+                ))?;
+            if ports.iter().any(|(net, _)| *net == pin.net) {
+                continue;
+            }
+            let direction = netlist
+                .find_net(pin.net)
+                .map(|net| port_direction(net, comp.ref_des))
+                .unwrap_or(PortDirection::InOut);
+            ports.push((pin.net, direction));
+        }
+    }
+
+    let mut out = String::new();
+
+    for include in includes.iter() {
+        writeln!(out, "`include \"{include}\"").unwrap();
+    }
+    if !includes.is_empty() {
+        writeln!(out).unwrap();
+    }
+
+    let port_list = ports
+        .iter()
+        .map(|(net, _)| net_identifier(&idents, *net))
+        .collect::<Result<Vec<_>, _>>()?;
+    writeln!(
+        out,
+        "module {}({});",
+        identifier(module_name),
+        port_list.join(", ")
+    )
+    .unwrap();
+
+    for (net, direction) in ports.iter() {
+        writeln!(out, "    {direction} {};", net_identifier(&idents, *net)?).unwrap();
+    }
+    if !ports.is_empty() {
+        writeln!(out).unwrap();
+    }
+
+    for net in netlist.nets.iter() {
+        if !ports.iter().any(|(name, _)| *name == net.name) {
+            writeln!(out, "    wire {};", net_identifier(&idents, net.name)?).unwrap();
+        }
+    }
+
+    for comp in netlist.components.iter() {
+        let Some(code) = comp.find_property(CODE_FIELD) else {
+            continue;
+        };
+        writeln!(out).unwrap();
+        writeln!(out, "    // {}", comp.ref_des).unwrap();
+        for line in expand_template(comp, code, &idents)?.lines() {
+            writeln!(out, "    {line}").unwrap();
+        }
+    }
+
+    writeln!(out, "endmodule").unwrap();
+
+    Ok(out)
+}
+
+/// The direction of a port is decided by what the rest of the design does with the net
+fn port_direction(net: &Net<'_>, port: RefDes<'_>) -> PortDirection {
+    let mut direction = PortDirection::Input;
+    for node in net.nodes.iter().filter(|node| node.ref_des != port) {
+        match node.typ {
+            PinType::Bidirectional => return PortDirection::InOut,
+            PinType::Output
+            | PinType::TriState
+            | PinType::PowerOutput
+            | PinType::OpenCollector
+            | PinType::OpenEmitter => direction = PortDirection::Output,
+            _ => {}
+        }
+    }
+    direction
+}
+
+fn expand_template(
+    comp: &Component<'_>,
+    template: &str,
+    idents: &HashMap<NetName<'_>, String>,
+) -> Result<String, VerilogError> {
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(pos) = find_placeholder(rest) {
+        out.push_str(&rest[..pos]);
+
+        let after = &rest[pos + 2..];
+        let end = after
+            .find(')')
+            .ok_or(VerilogError::UnterminatedTemplate(comp.ref_des.to_string()))?;
+
+        let args = after[..end]
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                let pin = comp
+                    .pins
+                    .iter()
+                    .find(|pin| pin.name.as_str() == name)
+                    .or_else(|| comp.find_pin(PinNum::from(name)))
+                    .ok_or(VerilogError::UnknownPin(
+                        comp.ref_des.to_string(),
+                        name.to_owned(),
+                    ))?;
+                net_identifier(idents, pin.net)
+            })
+            .collect::<Result<Vec<_>, VerilogError>>()?;
+
+        write!(
+            out,
+            "{}({})",
+            identifier(comp.ref_des.as_str()),
+            args.join(", ")
+        )
+        .unwrap();
+
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+
+    Ok(out)
+}
+
+/// Find the next `_(` which is not the tail of a longer identifier
+fn find_placeholder(s: &str) -> Option<usize> {
+    s.match_indices("_(")
+        .map(|(pos, _)| pos)
+        .find(|&pos| !s[..pos].ends_with(is_identifier_char))
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// The identifier of a net, which is missing if the net is not in the netlist
+fn net_identifier<'i, 'a>(
+    idents: &'i HashMap<NetName<'a>, String>,
+    net: NetName<'a>,
+) -> Result<&'i str, VerilogError> {
+    idents
+        .get(&net)
+        .map(String::as_str)
+        .ok_or_else(|| VerilogError::UnknownNet(net.to_string()))
+}
+
+/// Reserved words of Verilog 2005, which can only be used as escaped identifiers
+const KEYWORDS: &[&str] = &[
+    "always",
+    "and",
+    "assign",
+    "automatic",
+    "begin",
+    "buf",
+    "bufif0",
+    "bufif1",
+    "case",
+    "casex",
+    "casez",
+    "cell",
+    "cmos",
+    "config",
+    "deassign",
+    "default",
+    "defparam",
+    "design",
+    "disable",
+    "edge",
+    "else",
+    "end",
+    "endcase",
+    "endconfig",
+    "endfunction",
+    "endgenerate",
+    "endmodule",
+    "endprimitive",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "event",
+    "for",
+    "force",
+    "forever",
+    "fork",
+    "function",
+    "generate",
+    "genvar",
+    "highz0",
+    "highz1",
+    "if",
+    "ifnone",
+    "incdir",
+    "include",
+    "initial",
+    "inout",
+    "input",
+    "instance",
+    "integer",
+    "join",
+    "large",
+    "liblist",
+    "library",
+    "localparam",
+    "macromodule",
+    "medium",
+    "module",
+    "nand",
+    "negedge",
+    "nmos",
+    "nor",
+    "noshowcancelled",
+    "not",
+    "notif0",
+    "notif1",
+    "or",
+    "output",
+    "parameter",
+    "pmos",
+    "posedge",
+    "primitive",
+    "pull0",
+    "pull1",
+    "pulldown",
+    "pullup",
+    "pulsestyle_onevent",
+    "pulsestyle_ondetect",
+    "rcmos",
+    "real",
+    "realtime",
+    "reg",
+    "release",
+    "repeat",
+    "rnmos",
+    "rpmos",
+    "rtran",
+    "rtranif0",
+    "rtranif1",
+    "scalared",
+    "showcancelled",
+    "signed",
+    "small",
+    "specify",
+    "specparam",
+    "strong0",
+    "strong1",
+    "supply0",
+    "supply1",
+    "table",
+    "task",
+    "time",
+    "tran",
+    "tranif0",
+    "tranif1",
+    "tri",
+    "tri0",
+    "tri1",
+    "triand",
+    "trior",
+    "trireg",
+    "unsigned",
+    "use",
+    "uwire",
+    "vectored",
+    "wait",
+    "wand",
+    "weak0",
+    "weak1",
+    "while",
+    "wire",
+    "wor",
+    "xnor",
+    "xor",
+];
+
+/// Turn a name into a simple Verilog identifier, or an escaped identifier like `\wire ` for a reserved word
+fn identifier(name: &str) -> String {
+    let name = name.strip_prefix('/').unwrap_or(name);
+    let mut ident: String = name
+        .chars()
+        .map(|c| if is_identifier_char(c) { c } else { '_' })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident = format!("\\{ident} ");
+    }
+    ident
+}
+
+/// Assign a unique identifier to each net, which also differs from the module and the instances
+fn net_identifiers<'a>(netlist: &NetList<'a>, module_name: &str) -> HashMap<NetName<'a>, String> {
+    let mut used: HashSet<String> = netlist
+        .components
+        .iter()
+        .filter(|comp| comp.find_property(CODE_FIELD).is_some())
+        .map(|comp| identifier(comp.ref_des.as_str()))
+        .collect();
+    used.insert(identifier(module_name));
+    netlist
+        .nets
+        .iter()
+        .map(|net| {
+            let base = identifier(net.name.as_str());
+            let mut ident = base.clone();
+            let mut n = 1;
+            while !used.insert(ident.clone()) {
+                n += 1;
+                ident = format!("{base}_{n}");
+            }
+            (net.name, ident)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{component_mut, rename_net};
+
+    #[test]
+    fn can_export_verilog() {
+        let input = test_data!("kvt.net");
+        let netlist = NetList::parse(&input).unwrap();
+        let verilog = to_verilog(&netlist, "kvt").unwrap();
+
+        let expected = "\
+`include \"ttl.v\"
+
+module kvt(A, B, OUT);
+    input A;
+    input B;
+    output OUT;
+
+    wire GND;
+    wire Net__U1_Out_;
+    wire Net__U2_B_;
+    wire VCC;
+
+    // J1
+    // Do nothing
+
+    // U1
+    ttl_74LVC1G00 U1(A, B, Net__U1_Out_);
+
+    // U2
+    ttl_74LVC1G00 U2(Net__U1_Out_, Net__U2_B_, OUT);
+endmodule
+";
+        assert_eq!(verilog, expected);
+    }
+
+    #[test]
+    fn unknown_pin_in_template_is_an_error() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        let code = component_mut(&mut netlist, "U1")
+            .properties
+            .iter_mut()
+            .find(|prop| prop.name == CODE_FIELD)
+            .unwrap();
+        code.value = "ttl_74LVC1G00 _(A,C,Out);";
+        match to_verilog(&netlist, "kvt") {
+            Err(VerilogError::UnknownPin(ref_des, pin)) => {
+                assert_eq!(ref_des, "U1");
+                assert_eq!(pin, "C");
+            }
+            result => panic!("Expected an error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn reserved_words_are_escaped() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        rename_net(&mut netlist, "/OUT", "/output");
+        let verilog = to_verilog(&netlist, "module").unwrap();
+
+        assert!(verilog.contains("module \\module (A, B, \\output );"));
+        assert!(verilog.contains("    output \\output ;"));
+        assert!(verilog.contains("U2(Net__U1_Out_, Net__U2_B_, \\output );"));
+    }
+
+    #[test]
+    fn nets_do_not_reuse_module_or_instance_names() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        rename_net(&mut netlist, "/A", "/kvt");
+        rename_net(&mut netlist, "/OUT", "/U2");
+        let verilog = to_verilog(&netlist, "kvt").unwrap();
+
+        assert!(verilog.contains("module kvt(kvt_2, B, U2_2);"));
+        assert!(verilog.contains("U2(Net__U1_Out_, Net__U2_B_, U2_2);"));
+    }
+
+    #[test]
+    fn unknown_net_is_an_error() {
+        let input = test_data!("kvt.net");
+        let mut netlist = NetList::parse(&input).unwrap();
+        netlist.nets.retain(|net| net.name.as_str() != "/OUT");
+        match to_verilog(&netlist, "kvt") {
+            Err(VerilogError::UnknownNet(net)) => assert_eq!(net, "/OUT"),
+            result => panic!("Expected an error, got {result:?}"),
+        }
+    }
+}